@@ -0,0 +1,71 @@
+/*
+C interface of colombini_sat, following the IPASIR conventions of the SAT races.
+Link the cdylib built by cargo, e.g. -Ltarget/release -lcolombini_sat.
+Literals are non zero signed integers as in dimacs.
+Generated by cbindgen from src/ipasir.rs, do not edit.
+*/
+
+#ifndef ipasir_h_INCLUDED
+#define ipasir_h_INCLUDED
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+//returns the name and the version of the solver
+const char *ipasir_signature(void);
+
+//Creates a solver with no clauses, it must be freed with ipasir_release
+void *ipasir_init(void);
+
+//Frees the solver
+// # Safety
+// The handle must come from ipasir_init and can not be used after this call
+void ipasir_release(void *solver);
+
+//Adds a literal to the clause being built, 0 adds the clause to the formula
+// # Safety
+// The handle must come from ipasir_init
+void ipasir_add(void *solver, int32_t lit_or_zero);
+
+//Assumes the literal true for the next call to ipasir_solve only
+// # Safety
+// The handle must come from ipasir_init, the literal can not be 0
+void ipasir_assume(void *solver, int32_t lit);
+
+//Solves the formula under the assumptions given since the last call
+// # Returns
+// * 10 if the formula is satisfiable, 20 if it is not, 0 if the search was terminated or failed with an internal error
+// # Safety
+// The handle must come from ipasir_init
+int ipasir_solve(void *solver);
+
+//returns the value of the literal in the model found by the last ipasir_solve that returned 10:
+//the literal if it is true, its negation if it is false and 0 if the variable is not in the formula
+// # Safety
+// The handle must come from ipasir_init
+int32_t ipasir_val(void *solver, int32_t lit);
+
+//returns 1 if the assumption was used to prove the formula unsatisfiable in the last ipasir_solve that returned 20
+// # Safety
+// The handle must come from ipasir_init
+int ipasir_failed(void *solver, int32_t lit);
+
+//Sets a callback polled by ipasir_solve on every conflict, the search stops and returns 0 when it returns non zero.
+//A null callback removes the previous one
+// # Safety
+// The handle must come from ipasir_init, data is passed to the callback as it is
+void ipasir_set_terminate(void *solver, void *data, int (*terminate)(void*));
+
+//Accepted for compatibility, the learned clauses are not exported
+// # Safety
+// The handle must come from ipasir_init
+void ipasir_set_learn(void *_solver, void *_data, int _max_length, void (*_learn)(void*, int32_t*));
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ipasir_h_INCLUDED */
@@ -0,0 +1,203 @@
+use std::{collections::HashMap, fmt, ops::Not};
+use crate::{phi::Phi, clause::{Clause, Literal}, error::Error};
+
+/*
+Construction of formulas in memory, for applications that generate them instead of reading dimacs files.
+Variables are created by the builder, optionally with a name, and clauses can only use the variables created,
+so a formula built this way never needs the 1-based and 0-based conversions of Clause and Literal.
+*/
+
+///A literal of a formula under construction: a variable, numbered from 1 as in dimacs, or its negation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Lit(isize);
+
+impl Lit
+{
+    ///Creates the literal of a dimacs integer, the builder rejects 0 and variables it did not create
+    pub fn new(value: isize) -> Lit
+    {
+        Lit(value)
+    }
+
+    ///returns the variable, from 1
+    pub fn var(&self) -> usize
+    {
+        self.0.unsigned_abs()
+    }
+
+    pub fn is_positive(&self) -> bool
+    {
+        self.0 > 0
+    }
+
+    pub fn as_isize(&self) -> isize
+    {
+        self.0
+    }
+}
+
+impl Not for Lit
+{
+    type Output = Lit;
+
+    fn not(self) -> Lit
+    {
+        Lit(-self.0)
+    }
+}
+
+impl fmt::Display for Lit
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{}", self.0)
+    }
+}
+
+///Builds a formula clause by clause
+/// # Example
+/// ```
+/// use colombini_sat::{builder::Builder, solver::Solver};
+///
+/// let mut builder = Builder::new();
+/// let rain = builder.named_var("rain");
+/// let wet = builder.named_var("wet");
+/// builder.add_clause(&[!rain, wet]).unwrap();
+/// builder.add_clause(&[rain]).unwrap();
+/// let model = Solver::from_phi(builder.build()).solve().unwrap().unwrap();
+/// assert_eq!(model, vec![rain.as_isize(), wet.as_isize()]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Builder
+{
+    clauses: Vec<Clause>,
+    vars: usize,
+    names: HashMap<String, usize>,
+    //the name of every variable, from 0
+    var_names: Vec<Option<String>>
+}
+
+impl Builder
+{
+    pub fn new() -> Builder
+    {
+        Builder::default()
+    }
+
+    ///Creates a variable with no name
+    /// # Returns
+    /// * Its positive literal
+    pub fn new_var(&mut self) -> Lit
+    {
+        self.vars += 1;
+        self.var_names.push(None);
+        Lit(self.vars as isize)
+    }
+
+    ///returns the positive literal of the variable with the given name, creating the variable the first time
+    pub fn named_var(&mut self, name: &str) -> Lit
+    {
+        if let Some(var) = self.names.get(name) { return Lit(*var as isize); }
+        let lit = self.new_var();
+        self.names.insert(name.to_string(), lit.var());
+        self.var_names[lit.var() - 1] = Some(name.to_string());
+        lit
+    }
+
+    ///returns the positive literal of the variable with the given name, if it was created
+    pub fn lookup(&self, name: &str) -> Option<Lit>
+    {
+        self.names.get(name).map(|var| Lit(*var as isize))
+    }
+
+    ///returns the name of the variable of the literal, if it has one
+    pub fn name(&self, lit: Lit) -> Option<&str>
+    {
+        self.var_names.get(lit.var().checked_sub(1)?)?.as_deref()
+    }
+
+    pub fn num_vars(&self) -> usize
+    {
+        self.vars
+    }
+
+    pub fn num_clauses(&self) -> usize
+    {
+        self.clauses.len()
+    }
+
+    ///Adds a clause, the formula is unchanged if one of its literals is 0 or uses a variable that was not created
+    /// # Example
+    /// ```
+    /// use colombini_sat::builder::{Builder, Lit};
+    ///
+    /// let mut builder = Builder::new();
+    /// let x = builder.new_var();
+    /// assert!(builder.add_clause(&[x, Lit::new(0)]).is_err());
+    /// assert!(builder.add_clause(&[x, Lit::new(-2)]).is_err());
+    /// assert_eq!(builder.num_clauses(), 0);
+    /// ```
+    pub fn add_clause(&mut self, clause: &[Lit]) -> Result<(), Error>
+    {
+        if let Some(lit) = clause.iter().find(|l| l.0 == 0 || l.var() > self.vars)
+        {
+            return Err(Error::InvalidLiteral{literal: lit.0, vars: self.vars});
+        }
+        self.clauses.push(Clause::new(clause.iter().map(|l| Literal::from_isize(l.0)).collect()));
+        Ok(())
+    }
+
+    ///Returns the formula, the variables created are counted by vars() even if no clause uses them
+    pub fn build(self) -> Phi
+    {
+        let mut phi = Phi::from_clauses(self.clauses);
+        phi.declare_vars(self.vars);
+        phi
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{error::Error, solver::Solver};
+    use super::{Builder, Lit};
+
+    #[test]
+    fn build()
+    {
+        let mut builder = Builder::new();
+        let vars: Vec<Lit> = (0..4).map(|_| builder.new_var()).collect();
+        let x = builder.named_var("x");
+        assert_eq!(x, Lit::new(5));
+        assert_eq!(builder.named_var("x"), x);
+        assert_eq!(builder.lookup("x"), Some(x));
+        assert_eq!(builder.lookup("y"), None);
+        assert_eq!((builder.name(!x), builder.name(vars[0]), builder.name(Lit::new(9))), (Some("x"), None, None));
+
+        //exactly one of the first four variables, x only if the last one
+        builder.add_clause(&vars).unwrap();
+        for i in 0..4
+        {
+            for j in i+1..4
+            {
+                builder.add_clause(&[!vars[i], !vars[j]]).unwrap();
+            }
+        }
+        builder.add_clause(&[!x, vars[3]]).unwrap();
+        builder.add_clause(&[x]).unwrap();
+        assert!(matches!(builder.add_clause(&[Lit::new(6)]), Err(Error::InvalidLiteral{literal: 6, vars: 5})));
+        assert!(matches!(builder.add_clause(&[x, Lit::new(0)]), Err(Error::InvalidLiteral{literal: 0, ..})));
+
+        let phi = builder.build();
+        assert_eq!(phi.clauses().len(), 9);
+        let model = Solver::from_phi(phi).solve().unwrap().unwrap();
+        assert_eq!(model, vec![-1, -2, -3, 4, 5]);
+
+        //unused variables are part of the model
+        let mut builder = Builder::new();
+        let x = builder.new_var();
+        builder.new_var();
+        builder.add_clause(&[!x]).unwrap();
+        assert_eq!(Solver::from_phi(builder.build()).solve().unwrap().unwrap().len(), 2);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::error::Error;
+use std::str::FromStr;
+
+use crate::error::{Error, Position};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Literal
@@ -12,47 +14,37 @@ pub struct Literal
 
 impl Literal
 {
+    /// USE 1-BASED INDEXING
+    pub fn from_isize(v: isize) -> Literal
+    {
+        assert_ne!(v, 0);
+        Literal{index: v.unsigned_abs() - 1, value: v > 0, implicated: false, assigned: false}
+    }
+
     pub fn as_isize(&self) -> isize
     {
         if self.value { self.index as isize + 1 }
         else { -(self.index as isize + 1) }
     }
+
+    ///returns the opposite literal, without flags
+    pub fn negated(&self) -> Literal
+    {
+        Literal{index: self.index, value: !self.value, implicated: false, assigned: false}
+    }
 }
 
 impl std::fmt::Display for Clause
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
     {
-        
-        match self{
-            Clause::C3(l1,l2,l3) =>{
-                let literals = vec![*l1,*l2,*l3];
-                write!(f,"( ")?;
-                for lit in literals{
-                    if lit.implicated{write!(f,"\x1b[34m{} \x1b[0m",lit.as_isize())?}
-                    else if lit.assigned{write!(f,"\x1b[31m{} \x1b[0m",lit.as_isize())?}
-                    else {write!(f,"{} ",lit.as_isize())?}
-                }
-                write!(f,")")?;
-        },
-            Clause::C2(l1,l2) => {
-                let literals = vec![*l1,*l2];
-                write!(f,"( ")?;
-                for lit in literals{
-                    if lit.implicated{write!(f,"\x1b[34m{} \x1b[0m",lit.as_isize())?}
-                    else if lit.assigned{write!(f,"\x1b[31m{} \x1b[0m",lit.as_isize())?}
-                    else {write!(f,"{} ",lit.as_isize())?}
-                }
-                write!(f,")")?;
-            },
-            Clause::C1(l1) => {
-                if l1.implicated{write!(f,"(\x1b[34m{}\x1b[0m)",l1.as_isize())?}
-                else if l1.assigned{write!(f,"(\x1b[31m{}\x1b[0m)",l1.as_isize())?}
-                else {write!(f,"({})",l1.as_isize())?}
-            },
-            Clause::Empty => write!(f,"()")?
+        write!(f,"( ")?;
+        for lit in &self.literals{
+            if lit.implicated{write!(f,"\x1b[34m{} \x1b[0m",lit.as_isize())?}
+            else if lit.assigned{write!(f,"\x1b[31m{} \x1b[0m",lit.as_isize())?}
+            else {write!(f,"{} ",lit.as_isize())?}
         }
-        Ok(())
+        write!(f,")")
     }
 }
 
@@ -68,8 +60,9 @@ impl Implication
     /// USE 1-BASED INDEXING
     pub fn new(from: isize, to: isize) -> Implication
     {
-        Implication{from: Literal{index: from.abs() as usize - 1, value: from > 0, implicated: false,assigned: false}, 
-                    to: Literal{index: to.abs() as usize - 1, value: to > 0, implicated: true,assigned: false}}
+        let mut to = Literal::from_isize(to);
+        to.implicated = true;
+        Implication{from: Literal::from_isize(from), to}
     }
 
     pub fn to_clause(&self) -> Clause
@@ -77,485 +70,148 @@ impl Implication
         let mut l1 = self.from;
         let l2 = self.to;
         l1.value = !l1.value;
-        Clause::C2(l1,l2)
+        Clause::new(vec![l1,l2])
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Clause 
+/// A disjunction of any number of literals, a clause without literals is unsatisfiable
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clause 
 {
-    //clause with indefinite number of literals in it
-    C3(Literal, Literal, Literal),
-    C2(Literal, Literal),
-    C1(Literal),
-    Empty
+    pub literals: Vec<Literal>
 }
 
 impl Clause
 {
+    pub fn new(literals: Vec<Literal>) -> Clause
+    {
+        Clause{literals}
+    }
+
+    pub fn empty() -> Clause
+    {
+        Clause{literals: Vec::new()}
+    }
+
     // USE 1-BASED INDEXING
-    pub fn new_c3(v1: isize, v2: isize, v3: isize) -> Clause
+    pub fn new_cn(values: &[isize]) -> Clause
     {
-        assert_ne!(v1, 0);
-        assert_ne!(v2, 0);
-        assert_ne!(v3, 0);
+        Clause{literals: values.iter().map(|v| Literal::from_isize(*v)).collect()}
+    }
 
-        Clause::C3(Literal{index: v1.abs() as usize - 1, value: v1 > 0, implicated: false, assigned: false}, 
-                   Literal{index: v2.abs() as usize - 1, value: v2 > 0, implicated: false, assigned: false}, 
-                   Literal{index: v3.abs() as usize - 1, value: v3 > 0, implicated: false, assigned: false})
+    // USE 1-BASED INDEXING
+    pub fn new_c3(v1: isize, v2: isize, v3: isize) -> Clause
+    {
+        Clause::new_cn(&[v1, v2, v3])
     }
 
     // USE 1-BASED INDEXING
     pub fn new_c2(v1: isize, v2: isize) -> Clause
     {
-        assert_ne!(v1, 0);
-        assert_ne!(v2, 0);
-
-        Clause::C2(Literal{index: v1.abs() as usize - 1, value: v1 > 0, implicated: false, assigned: false}, 
-                   Literal{index: v2.abs() as usize - 1, value: v2 > 0, implicated: false, assigned: false})
+        Clause::new_cn(&[v1, v2])
     }
 
     // USE 1-BASED INDEXING
     pub fn new_c1(v1: isize) -> Clause
     {
-        assert_ne!(v1, 0);
-
-        Clause::C1(Literal{index: v1.abs() as usize - 1, value: v1 > 0, implicated: false, assigned: false})
+        Clause::new_cn(&[v1])
     }
 
     pub fn new_c1_implicated(v1: isize) -> Clause
     {
-        assert_ne!(v1, 0);
+        let mut lit = Literal::from_isize(v1);
+        lit.implicated = true;
+        Clause{literals: vec![lit]}
+    }
 
-        Clause::C1(Literal{index: v1.abs() as usize - 1, value: v1 > 0, implicated: true, assigned: false})
+    pub fn len(&self) -> usize
+    {
+        self.literals.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.literals.is_empty()
     }
 
     pub fn get_literal(&self, index: usize) -> Option<Literal>
     {
-        match self
-        {
-            Clause::C3(l1, l2, l3) => 
-            {
-                if l1.index == index { Some(*l1) }
-                else if l2.index == index { Some(*l2) }
-                else if l3.index == index { Some(*l3) }
-                else { None }
-            },
-            Clause::C2(l1, l2) => 
-            {
-                if l1.index == index { Some(*l1) }
-                else if l2.index == index { Some(*l2) }
-                else { None }
-            },
-            Clause::C1(l1) => 
-            {
-                if l1.index == index { Some(*l1) }
-                else { None }
-            },
-            Clause::Empty => None
-        }
+        self.literals.iter().find(|l| l.index == index).copied()
     }
 
     pub fn is_implicated(&self)->bool{
-        match self
-        {
-            Clause::C2(l1, l2) => 
-            {
-                if l1.implicated || l2.implicated {
-                    return true;
-                }
-            },
-            Clause::C1(l1) => 
-            {
-                if l1.implicated {
-                    return true;
-                }
-            },
-            _ => {}
-        }
-        false
+        self.literals.iter().any(|l| l.implicated)
     }
 
     pub fn adapt(&self)->Option<Clause>{
-        match self
-        {
-            Clause::C3(l1,l2,l3)=>{
-                if l1.implicated{None}
-                else if l2.implicated{None}
-                else if l3.implicated{None}
-                else if l1.assigned{Clause::C2(*l2,*l3).adapt()}
-                else if l2.assigned{Clause::C2(*l1,*l3).adapt()}
-                else if l3.assigned{Clause::C2(*l1,*l2).adapt()}
-                else {Some(*self)}
-            }
-            Clause::C2(l1,l2)=>{
-                if l1.implicated{None}
-                else if l2.implicated{None}
-                else if l1.assigned{Clause::C1(*l2).adapt()}
-                else if l2.assigned{Clause::C1(*l1).adapt()}
-                else {Some(*self)}
-            }
-            Clause::C1(l1) => {
-                if l1.implicated{None}
-                else if l1.assigned{Some(Clause::Empty)}
-                else {Some(*self)}
-            }
-            Clause::Empty => Some(Clause::Empty),
-        }
+        if self.is_implicated() {return None}
+        Some(Clause{literals: self.literals.iter().filter(|l| !l.assigned).copied().collect()})
     }
 
     pub fn literals_vector(&self) -> Vec<Literal>
     {
-        match self
-        {
-            Clause::C3(l1, l2, l3) => vec![*l1, *l2, *l3],
-            Clause::C2(l1, l2) => vec![*l1, *l2],
-            Clause::C1(l1) => vec![*l1],
-            Clause::Empty => Vec::new()
-        }
+        self.literals.clone()
     }
 
     pub fn get_variables(&self) -> Vec<Literal>
     {
-        match self
-        {
-            Clause::C3(l1, l2, l3) => vec![*l1, *l2, *l3],
-            Clause::C2(l1, l2) => vec![*l1, *l2],
-            Clause::C1(l1) => vec![*l1],
-            Clause::Empty => Vec::new()
-        }
+        self.literals.clone()
     }
 
-    pub fn from_str(s: &str) -> Result<Clause,Box<dyn std::error::Error>>
-    {
-        //split the line in words
-        let new_s = s.trim();
-        let words: Vec<&str> = new_s.split(" ").collect();
-        if words.len() > 4 || words.len() < 2
-        {
-            Err(Error::new("Clause is not 3-SAT or empty").into())
-        }
-        else if words.len() == 2
-        {
-            let (v1, v2) = 
-                (words[0].parse::<isize>()?,
-                words[1].parse::<isize>()?);
-            if v2 != 0 { Err(Error::new("Error while parsing clause").into()) }
-            else if v1 == 0 { Err(Error::new("Error while parsing clause").into()) }
-            else { Ok(Clause::new_c1(v1)) }
-        }
-        else if words.len() == 3
-        {
-            //convert the words to numbers
-            let (v1, v2, v3) = 
-                (words[0].parse::<isize>()?,
-                words[1].parse::<isize>()?, 
-                words[2].parse::<isize>()?);
-            // v4 must be 0
-            if v3 != 0 { Err(Error::new("Error while parsing clause").into()) }
-            else if v1 == 0 || v2 == 0 { Err(Error::new("Error while parsing clause").into()) }
-            else { Ok(Clause::new_c2(v1,v2)) }
-        }
-        else 
-        {
-            //convert the words to numbers
-            let (v1, v2, v3, v4) = 
-                (words[0].parse::<isize>()?,
-                words[1].parse::<isize>()?, 
-                words[2].parse::<isize>()?, 
-                words[3].parse::<isize>()?);
-            // v4 must be 0
-            if v4 != 0 { Err(Error::new("Error while parsing clause").into()) }
-            // v1, v2, v3 must not be 0
-            else if v1 == 0 || v2 == 0 || v3 == 0 { Err(Error::new("Error while parsing clause").into()) }
-            else { Ok(Clause::new_c3(v1,v2,v3)) }
-        }
-
-    }
     pub fn max_literal(&self) -> usize 
     {
-        match self
-        {
-            Clause::C3(l1,l2,l3) => 
-            {
-                if l1.index > l2.index 
-                {
-                    if l1.index > l3.index { l1.index } else { l3.index }
-                }
-                else
-                {
-                    if l2.index > l3.index { l2.index } else { l3.index }
-                }
-            }, 
-            Clause::C2(l1,l2) => 
-            {
-                if l1.index > l2.index { l1.index } else { l2.index }
-            },
-            Clause::C1(l1) => 
-            {
-                l1.index
-            },
-            Clause::Empty => 
-            {
-                0
-            }
-        }
+        self.literals.iter().map(|l| l.index).max().unwrap_or(0)
     }
 
     pub fn eval(&self, values: &[bool]) -> bool
     {
-        match self
-        {
-            Clause::C3(l1,l2,l3) => 
-            {
-                (if l1.value == true { values[l1.index] } else { !values[l1.index] })
-                ||
-                (if l2.value == true { values[l2.index] } else { !values[l2.index] })
-                ||
-                (if l3.value == true { values[l3.index] } else { !values[l3.index] })
-            }, 
-            Clause::C2(l1,l2) => 
-            {
-                (if l1.value == true { values[l1.index] } else { !values[l1.index] })
-                ||
-                (if l2.value == true { values[l2.index] } else { !values[l2.index] })
-            },
-            Clause::C1(l1) => 
-            {
-                if l1.value == true { values[l1.index] } else { !values[l1.index] }
-            },
-            Clause::Empty => 
-            {
-                false
-            }
-        }
+        self.literals.iter().any(|l| values[l.index] == l.value)
     }
     
     pub fn reduce(&self, values: &[Option<bool>]) -> Option<Clause>
     {
-        match self
+        if self.is_implicated() {return None}
+        if self.is_empty() || self.literals.iter().all(|l| l.assigned) {return Some(Clause::empty())}
+        let mut literals = self.literals.clone();
+        for i in 0..literals.len()
         {
-            Clause::C3(l1,l2,l3) => 
-            {
-                if l1.implicated || l2.implicated || l3.implicated {return None}
-                if l1.assigned && l2.assigned && l3.assigned {return Some(Clause::Empty)}
-                match values[l1.index] {
-                    None => {
-                        match values[l2.index] {
-                            None => match values[l3.index] {
-                                None => Some(*self),
-                                Some(v) => {
-                                    let lit = Literal{index: l3.index, value: l3.value, implicated: false, assigned: true};
-                                    match l3.value 
-                                    {
-                                        true => if v { None } else { Some(Clause::C3(*l1,*l2,lit)) }
-                                        false => if v { Some(Clause::C3(*l1,*l2,lit)) } else { None }    
-                                    }
-                                }
-                            },
-                            Some(v) => {
-                                let lit = Literal{index: l2.index, value: l2.value, implicated: false, assigned: true};
-                                let mut next_reduction = values.clone().to_owned();
-                                next_reduction[l2.index] = None;  
-                                match l2.value 
-                                {
-                                    true => if v { None } else { Clause::C3(*l1,lit,*l3).reduce(&next_reduction) }
-                                    false => if v { Clause::C3(*l1,lit,*l3).reduce(&next_reduction) } else { None }    
-                                }
-                            }
-                        }
-                    },
-                    Some(v) => {
-                        let lit = Literal{index: l1.index, value: l1.value, implicated: false, assigned: true};
-                        let mut next_reduction = values.clone().to_owned();
-                        next_reduction[l1.index] = None;  
-                        match l1.value 
-                        {
-                            true => if v { None } else {Clause::C3(lit,*l2,*l3).reduce(&next_reduction)}
-                            false => if v { Clause::C3(lit,*l2,*l3).reduce(&next_reduction) } else { None }    
-                        }
-                    }
-                }
-            },
-            Clause::C2(l1,l2) => 
-            {
-                if l1.implicated || l2.implicated{return None}
-                if l1.assigned && l2.assigned{return Some(Clause::Empty)}
-                match values[l1.index] {
-                    None => {
-                        match values[l2.index] {
-                            None => Some(*self),
-                            Some(v) => {
-                                let lit = Literal{index: l2.index, value: l2.value, implicated: false, assigned: true};
-                                match l2.value 
-                                {
-                                    true => if v { None } else { Some(Clause::C2(*l1,lit)) }
-                                    false => if v { Some(Clause::C2(*l1,lit)) } else { None }    
-                                }
-                            }
-                        }
-                    },
-                    Some(v) => {
-                        let lit = Literal{index: l1.index, value: l1.value, implicated: false, assigned: true};
-                        let mut next_reduction = values.clone().to_owned();
-                        next_reduction[l1.index] = None;  
-                        match l1.value 
-                        {
-                            true => if v { None } else { Clause::C2(lit,*l2).reduce(&next_reduction) }
-                            false => if v { Clause::C2(lit,*l2).reduce(&next_reduction) } else { None }    
-                        }
-                    }
-                }
-            },
-            Clause::C1(l1) => 
-            {
-                if l1.implicated{return None}
-                if l1.assigned {return Some(Clause::Empty)}
-                match values[l1.index] {
-                    None => Some(*self),
-                    Some(v) => {
-                        let lit = Literal{index: l1.index, value: l1.value, implicated: false, assigned: true};
-                        match l1.value 
-                        {
-                            true => if v { None } else { Some(Clause::C1(lit)) }
-                            false => if v { Some(Clause::C1(lit)) } else { None }    
-                        }
-                    }
-                }
-            },
-            Clause::Empty => 
+            if let Some(v) = values[literals[i].index]
             {
-                return Some(Clause::Empty);
-            } 
+                if v == literals[i].value {return None}
+                literals[i].assigned = true;
+                //the remaining literals were already falsified by a previous reduction
+                if i + 1 < literals.len() && literals.iter().all(|l| l.assigned) {return Some(Clause::empty())}
+            }
         }
+        Some(Clause{literals})
     }
 
     pub fn contains(&self, index : usize)-> bool{
-        let mut result: bool = false;
-        match self
-        {
-            Clause::C3(l1,l2,l3) => 
-            {
-                if l1.index == index || l2.index == index || l3.index == index {
-                    result = true;
-                }
-            }, 
-            Clause::C2(l1,l2) => 
-            {
-                if l1.index == index || l2.index == index {
-                    result =  true;
-                }
-            },
-            Clause::C1(l1) => 
-            {
-                if l1.index == index {
-                    result = true;
-                }
-            },
-            Clause::Empty => 
-            {
-                
-            }
-        }
-        result
+        self.literals.iter().any(|l| l.index == index)
     }
 
     pub fn remove(&self, literal: usize)->Clause{
         //return a clause without the literal
-        match self
+        match self.literals.iter().position(|l| l.index == literal)
         {
-            Clause::C3(l1,l2,l3) => 
-            {
-                if l1.index == literal {
-                    return Clause::C2(*l2,*l3);
-                }
-                else if l2.index == literal {
-                    return Clause::C2(*l1,*l3);
-                }
-                else if l3.index == literal {
-                    return Clause::C2(*l1,*l2);
-                }
-                else {
-                    return Clause::C3(*l1,*l2,*l3);
-                }
-            }, 
-            Clause::C2(l1,l2) => 
-            {
-                if l1.index == literal {
-                    return Clause::C1(*l2);
-                }
-                else if l2.index == literal {
-                    return Clause::C1(*l1);
-                }
-                else {
-                    return Clause::C2(*l1,*l2);
-                }
-            },
-            Clause::C1(l1) => 
-            {
-                if l1.index == literal {
-                    return Clause::Empty;
-                }
-                else {
-                    return Clause::C1(*l1);
-                }
+            Some(position) => {
+                let mut literals = self.literals.clone();
+                literals.remove(position);
+                Clause{literals}
             },
-            Clause::Empty => 
-            {
-                return Clause::Empty;
-            }
+            None => self.clone()
         }
     }
 
     pub fn invert_literal(&mut self, index : usize){
-        match self
-        {
-            Clause::C3(l1,l2,l3) => 
-            {
-                if l1.index == index {
-                    l1.value = !l1.value;
-                }
-                else if l2.index == index {
-                    l2.value = !l2.value;
-                }
-                else if l3.index == index {
-                    l3.value = !l3.value;
-                }
-            }, 
-            Clause::C2(l1,l2) => 
-            {
-                if l1.index == index {
-                    l1.value = !l1.value;
-                }
-                else if l2.index == index {
-                    l2.value = !l2.value;
-                }
-            },
-            Clause::C1(l1) => 
-            {
-                if l1.index == index {
-                    l1.value = !l1.value;
-                }
-            },
-            Clause::Empty => {}
+        if let Some(lit) = self.literals.iter_mut().find(|l| l.index == index){
+            lit.value = !lit.value;
         }
     }
 
     pub fn update_implications(&mut self, lit: &Literal){
-        match self{
-            Clause::C3(lit1,lit2,lit3) => {
-                if lit1.index==lit.index && lit1.value==lit.value {lit1.implicated=true}else if lit1.index==lit.index{lit1.assigned=true}
-                if lit2.index==lit.index && lit2.value==lit.value {lit2.implicated=true}else if lit2.index==lit.index{lit2.assigned=true}
-                if lit3.index==lit.index && lit3.value==lit.value {lit3.implicated=true}else if lit3.index==lit.index{lit3.assigned=true}
-            }
-            Clause::C2(lit1,lit2) => {
-                if lit1.index==lit.index && lit1.value==lit.value {lit1.implicated=true}else if lit1.index==lit.index{lit1.assigned=true}
-                if lit2.index==lit.index && lit2.value==lit.value {lit2.implicated=true}else if lit2.index==lit.index{lit2.assigned=true}
-            }
-            Clause::C1(lit1) => {
-                if lit1.index==lit.index && lit1.value==lit.value {lit1.implicated=true}else if lit1.index==lit.index{lit1.assigned=true}
-            }
-            Clause::Empty => {}
+        for l in self.literals.iter_mut(){
+            if l.index==lit.index && l.value==lit.value {l.implicated=true}else if l.index==lit.index{l.assigned=true}
         }
     }
 
@@ -567,50 +223,87 @@ impl Clause
     /// * `Vec<Clause>` - A vector of 3-SAT clauses
     pub fn from_k_clause(clause :Vec<Literal>,last_index: usize) -> Vec<Clause>{
 
-        let mut result: Vec<Clause> = Vec::new();
-        match clause.len(){
-            1 => {
-                result.push(Clause::C1(clause[0]));
-                return result
-            },
-            2 => {
-                result.push(Clause::C2(clause[0],clause[1]));
-                return result
-            },
-            3 => {
-                result.push(Clause::C3(clause[0],clause[1],clause[2]));
-                return result
-            },
-            _ => {}
+        if clause.len() <= 3{
+            return vec![Clause::new(clause)];
         }
 
+        let mut result: Vec<Clause> = Vec::new();
         let mut literals = clause.clone();
         
-        let mut foo_vars = 0;
+        let mut new_vars = 0;
 
         let lit1 = literals.pop().unwrap();
         let lit2 = literals.pop().unwrap();
-        let foo = Literal{index: last_index+foo_vars, value: true, implicated: false, assigned: false};
+        let link = Literal{index: last_index+new_vars, value: true, implicated: false, assigned: false};
 
-        result.push(Clause::C3(lit1,lit2,foo));
+        result.push(Clause::new(vec![lit1,lit2,link]));
 
         while literals.len() > 2{
-            let foo1 = Literal{index: last_index+foo_vars, value: false, implicated: false, assigned: false};
+            let link1 = Literal{index: last_index+new_vars, value: false, implicated: false, assigned: false};
             let lit = literals.pop().unwrap();
-            foo_vars += 1;
-            let foo2 = Literal{index: last_index+foo_vars, value: true, implicated: false, assigned: false};
-            result.push(Clause::C3(foo1,lit,foo2));
+            new_vars += 1;
+            let link2 = Literal{index: last_index+new_vars, value: true, implicated: false, assigned: false};
+            result.push(Clause::new(vec![link1,lit,link2]));
         }
 
-        let foo = Literal{index: last_index+foo_vars, value: false, implicated: false, assigned: false};
+        let link = Literal{index: last_index+new_vars, value: false, implicated: false, assigned: false};
         let lit1 = literals.pop().unwrap();
         let lit2 = literals.pop().unwrap();
-        result.push(Clause::C3(foo,lit1,lit2));
+        result.push(Clause::new(vec![link,lit1,lit2]));
 
         result
     }
 }
 
+///splits a line in words, together with the column where each word starts
+pub(crate) fn words(line: &str) -> Vec<(usize, &str)>
+{
+    let mut ret = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, c) in line.char_indices()
+    {
+        match (c.is_whitespace(), start)
+        {
+            (true, Some(s)) => {
+                ret.push((s + 1, &line[s..i]));
+                start = None;
+            },
+            (false, None) => { start = Some(i); },
+            _ => {}
+        }
+    }
+    if let Some(s) = start { ret.push((s + 1, &line[s..])); }
+    ret
+}
+
+///Parses a line of a dimacs file, errors are reported on line 1
+impl FromStr for Clause
+{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Clause,Error>
+    {
+        let mut values: Vec<isize> = Vec::new();
+        let mut terminated = false;
+        for (column, word) in words(s)
+        {
+            let at = Position::new(1, column);
+            //no literal can follow the terminating 0
+            if terminated { return Err(Error::BadLiteral{at, token: word.to_string()}); }
+            match word.parse::<isize>()
+            {
+                Ok(0) if values.is_empty() => { return Err(Error::EmptyClause{at}); },
+                Ok(0) => { terminated = true; },
+                Ok(value) => { values.push(value); },
+                Err(_) => { return Err(Error::BadLiteral{at, token: word.to_string()}); }
+            }
+        }
+        if values.is_empty() { return Err(Error::EmptyClause{at: Position::new(1, 1)}); }
+        if !terminated { return Err(Error::UnterminatedClause{at: Position::new(1, s.trim_end().len() + 1)}); }
+        Ok(Clause::new_cn(&values))
+    }
+}
+
 #[cfg(test)]
 mod tests
 {
@@ -618,11 +311,11 @@ mod tests
     #[test]
     fn eval()
     {
-        let c = Clause::C3(
+        let c = Clause::new(vec![
             Literal{index: 0, value: true, implicated: false, assigned: false}, 
             Literal{index: 1, value: false, implicated: false, assigned: false},
             Literal{index: 2, value: true, implicated: false, assigned: false}
-        );
+        ]);
 
         let mut values = [true, true, false];
         assert_eq!(c.eval(&values), true);
@@ -635,74 +328,104 @@ mod tests
     #[test]
     fn reduce()
     {
-        let c = Clause::C3(
+        let c = Clause::new(vec![
             Literal{index: 0, value: true, implicated: false, assigned: false}, 
             Literal{index: 1, value: false, implicated: false, assigned: false}, 
             Literal{index: 2, value: true, implicated: false, assigned: false}
-        );
+        ]);
 
         let mut values: [Option<bool>; 3] = [None, None, None];
-        assert_eq!(c.reduce(&values), Some(c));
+        assert_eq!(c.reduce(&values), Some(c.clone()));
         values = [ Some(true), None, None];
         assert_eq!(c.reduce(&values), None);
         values = [Some(false), Some(true), Some(false)];
-        assert_eq!(c.reduce(&values), Some(Clause::C3(
+        assert_eq!(c.reduce(&values), Some(Clause::new(vec![
             Literal{index: 0, value: true, implicated: false, assigned: true}, 
             Literal{index: 1, value: false, implicated: false, assigned: true}, 
-            Literal{index: 2, value: true, implicated: false, assigned: true})
+            Literal{index: 2, value: true, implicated: false, assigned: true}])
         ));
         values = [Some(false), None, Some(false)];
-        assert_eq!(c.reduce(&values), Some(Clause::C3(
+        assert_eq!(c.reduce(&values), Some(Clause::new(vec![
             Literal{index: 0, value: true, implicated: false, assigned: true}, 
             Literal{index: 1, value: false, implicated: false, assigned: false}, 
-            Literal{index: 2, value: true, implicated: false, assigned: true})
+            Literal{index: 2, value: true, implicated: false, assigned: true}])
         ));
 
     }
 
+    #[test]
+    fn from_str()
+    {
+        let c: Clause = "1 -2  3 -4\t5 0".parse().unwrap();
+        assert_eq!(c, Clause::new_cn(&[1,-2,3,-4,5]));
+        assert_eq!(c.len(), 5);
+        assert!(matches!("1 2 3".parse::<Clause>(), Err(Error::UnterminatedClause{at: Position{line: 1, column: 6}})));
+        assert!(matches!("1 0 3 0".parse::<Clause>(), Err(Error::BadLiteral{at: Position{line: 1, column: 5}, ..})));
+        assert!(matches!("0".parse::<Clause>(), Err(Error::EmptyClause{..})));
+        match "1 -x 0".parse::<Clause>()
+        {
+            Err(Error::BadLiteral{at, token}) => assert_eq!((at.column, token.as_str()), (3, "-x")),
+            _ => panic!("expected a bad literal")
+        }
+    }
+
+    #[test]
+    fn reduce_wide()
+    {
+        let c = Clause::new_cn(&[1,-2,3,-4,5]);
+        assert!(c.eval(&[false, true, false, true, true]));
+        assert!(!c.eval(&[false, true, false, true, false]));
+
+        let values = [Some(false), None, Some(false), Some(true), None];
+        let reduced = c.reduce(&values).unwrap();
+        assert_eq!(reduced.adapt(), Some(Clause::new_cn(&[-2,5])));
+        let values = [None, Some(false), None, None, None];
+        assert_eq!(c.reduce(&values), None);
+    }
+
     #[test]
     fn clause_invert_literal()
     {
-        let mut c3 = Clause::C3(
+        let mut c3 = Clause::new(vec![
             Literal{index: 0, value: true, implicated: false, assigned: false}, 
             Literal{index: 1, value: false, implicated: false, assigned: false}, 
             Literal{index: 2, value: true, implicated: false, assigned: false}
-        );
+        ]);
 
-        let mut c2 = Clause::C2(
+        let mut c2 = Clause::new(vec![
             Literal{index: 0, value: true, implicated: false, assigned: false}, 
             Literal{index: 1, value: false, implicated: false, assigned: false}
-        );
+        ]);
 
-        let mut c1 = Clause::C1(
+        let mut c1 = Clause::new(vec![
             Literal{index: 0, value: true, implicated: false, assigned: false}
-        );
+        ]);
 
         c3.invert_literal(0);
         c2.invert_literal(0);
         c1.invert_literal(0);
 
-        assert_eq!(c3, Clause::C3(
+        assert_eq!(c3, Clause::new(vec![
             Literal{index: 0, value: false, implicated: false, assigned: false}, 
             Literal{index: 1, value: false, implicated: false, assigned: false}, 
             Literal{index: 2, value: true, implicated: false, assigned: false}
-        ));
+        ]));
 
-        assert_eq!(c2, Clause::C2(
+        assert_eq!(c2, Clause::new(vec![
             Literal{index: 0, value: false, implicated: false, assigned: false}, 
             Literal{index: 1, value: false, implicated: false, assigned: false}
-        ));
+        ]));
 
-        assert_eq!(c1, Clause::C1(
+        assert_eq!(c1, Clause::new(vec![
             Literal{index: 0, value: false, implicated: false, assigned: false}
-        ));
+        ]));
 
         c3.invert_literal(0);
-        assert_eq!(c3, Clause::C3(
+        assert_eq!(c3, Clause::new(vec![
             Literal{index: 0, value: true, implicated: false, assigned: false}, 
             Literal{index: 1, value: false, implicated: false, assigned: false}, 
             Literal{index: 2, value: true, implicated: false, assigned: false}
-        ));
+        ]));
     }
 
     #[test]
@@ -716,16 +439,16 @@ mod tests
 
         let result = Clause::from_k_clause(c,4);
 
-        let correct = vec![Clause::C3(
+        let correct = vec![Clause::new(vec![
             Literal{index: 3, value: true, implicated: false, assigned: false}, 
             Literal{index: 2, value: true, implicated: false, assigned: false}, 
             Literal{index: 4, value: true, implicated: false, assigned: false}
-        ),
-        Clause::C3(
+        ]),
+        Clause::new(vec![
             Literal{index: 4, value: false, implicated: false, assigned: false}, 
             Literal{index: 1, value: true, implicated: false, assigned: false}, 
             Literal{index: 0, value: true, implicated: false, assigned: false}
-        )];
+        ])];
         assert_eq!(result.len(), 2);
         assert_eq!(result,correct);
 
@@ -734,21 +457,21 @@ mod tests
 
         let result = Clause::from_k_clause(c,5);
 
-        let correct = vec![Clause::C3(
+        let correct = vec![Clause::new(vec![
             Literal{index: 4, value: true, implicated: false, assigned: false}, 
             Literal{index: 3, value: true, implicated: false, assigned: false}, 
             Literal{index: 5, value: true, implicated: false, assigned: false}
-        ),
-        Clause::C3(
+        ]),
+        Clause::new(vec![
             Literal{index: 5, value: false, implicated: false, assigned: false}, 
             Literal{index: 2, value: true, implicated: false, assigned: false}, 
             Literal{index: 6, value: true, implicated: false, assigned: false}
-        ),
-        Clause::C3(
+        ]),
+        Clause::new(vec![
             Literal{index: 6, value: false, implicated: false, assigned: false}, 
             Literal{index: 1, value: true, implicated: false, assigned: false}, 
             Literal{index: 0, value: true, implicated: false, assigned: false}
-        )];
+        ])];
         assert_eq!(result.len(), 3);
         assert_eq!(result,correct);
 
@@ -0,0 +1,320 @@
+use std::{collections::VecDeque, io::BufRead};
+use crate::{clause::{Clause, words}, error::{Error, Position}};
+
+/*
+Streaming reader of dimacs cnf formulas.
+The input is read one line at a time from any BufRead, so files, stdin, pipes and byte slices can be parsed
+without loading them in memory. A clause ends at its 0, so a clause can span many lines and a line can hold many clauses.
+Lines starting with c are comments, kept without the c so that they can be written back, and a line starting with % ends the formula as in the SATLIB benchmarks.
+Some files close the formula with a lone 0 instead, so a 0 found after all the declared clauses is ignored.
+In lenient mode the deviations from the format are collected as warnings instead of stopping the parser.
+*/
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode
+{
+    ///any deviation from the format or from the header is an error
+    #[default]
+    Strict,
+    ///tokens that are not literals, a missing or repeated header, empty and unterminated clauses
+    ///and formulas that do not match the header are accepted with a warning
+    Lenient
+}
+
+///The "p cnf <variables> <clauses>" line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header
+{
+    pub vars: usize,
+    pub clauses: usize,
+    pub at: Position
+}
+
+///Iterator over the clauses of a dimacs formula, together with the position of their first literal
+/// # Example
+/// ```
+/// use colombini_sat::{dimacs::Parser, clause::Clause};
+///
+/// let mut parser = Parser::new("p cnf 3 2\n1 -2\n 3 0 -1 0\n".as_bytes());
+/// assert_eq!(parser.next().unwrap().unwrap().0, Clause::new_c3(1,-2,3));
+/// assert_eq!(parser.next().unwrap().unwrap().0, Clause::new_c1(-1));
+/// assert!(parser.next().is_none());
+/// assert_eq!(parser.header().unwrap().vars, 3);
+/// ```
+pub struct Parser<R: BufRead>
+{
+    reader: R,
+    buffer: String,
+    //number of lines read
+    line: usize,
+    header: Option<Header>,
+    //number of clauses read
+    clauses: usize,
+    //the literals of the clause being read and the position of the first one
+    pending: Vec<isize>,
+    start: Position,
+    ready: VecDeque<(Clause, Position)>,
+    //returned after the clauses read before it
+    error: Option<Error>,
+    finished: bool,
+    mode: ParseMode,
+    warnings: Vec<Error>,
+    comments: Vec<String>
+}
+
+impl<R: BufRead> Parser<R>
+{
+    pub fn new(reader: R) -> Parser<R>
+    {
+        Parser::with_mode(reader, ParseMode::Strict)
+    }
+
+    pub fn with_mode(reader: R, mode: ParseMode) -> Parser<R>
+    {
+        Parser{
+            reader,
+            buffer: String::new(),
+            line: 0,
+            header: None,
+            clauses: 0,
+            pending: Vec::new(),
+            start: Position::new(1, 1),
+            ready: VecDeque::new(),
+            error: None,
+            finished: false,
+            mode,
+            warnings: Vec::new(),
+            comments: Vec::new()
+        }
+    }
+
+    ///returns the header, once it has been read
+    pub fn header(&self) -> Option<Header>
+    {
+        self.header
+    }
+
+    ///returns the deviations from the format accepted in lenient mode
+    pub fn warnings(&self) -> &[Error]
+    {
+        &self.warnings
+    }
+
+    ///returns the text of the comments read so far, without the leading c
+    pub fn comments(&self) -> &[String]
+    {
+        &self.comments
+    }
+
+    pub fn into_warnings(self) -> Vec<Error>
+    {
+        self.warnings
+    }
+
+    ///Records the error as a warning in lenient mode
+    /// # Returns
+    /// * The error in strict mode
+    fn tolerate(&mut self, e: Error) -> Result<(), Error>
+    {
+        match self.mode
+        {
+            ParseMode::Strict => Err(e),
+            ParseMode::Lenient => {
+                self.warnings.push(e);
+                Ok(())
+            }
+        }
+    }
+
+    ///Reads the next line
+    /// # Returns
+    /// * false at the end of the input
+    fn read_line(&mut self) -> Result<bool, Error>
+    {
+        self.buffer.clear();
+        let read = self.reader.read_line(&mut self.buffer).map_err(|source| Error::Io{at: Some(Position::new(self.line + 1, 1)), source})?;
+        if read == 0 { return Ok(false); }
+        self.line += 1;
+        let line = std::mem::take(&mut self.buffer);
+        let result = self.parse_line(&line);
+        self.buffer = line;
+        result.map(|_| true)
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), Error>
+    {
+        let words = words(line);
+        let first = match words.first()
+        {
+            Some((_, first)) => *first,
+            None => return Ok(())
+        };
+        if first.starts_with('c')
+        {
+            let text = &line.trim()[1..];
+            //the separator after the c is not part of the comment
+            self.comments.push(text.strip_prefix([' ', '\t']).unwrap_or(text).to_string());
+            return Ok(());
+        }
+        if first.starts_with('%')
+        {
+            self.finished = true;
+            return Ok(());
+        }
+        if first.starts_with('p')
+        {
+            let at = Position::new(self.line, words[0].0);
+            let sizes = match words.as_slice()
+            {
+                [(_, "p"), (_, "cnf"), (_, vars), (_, clauses)] => vars.parse().ok().zip(clauses.parse().ok()),
+                _ => None
+            };
+            match sizes
+            {
+                Some((vars, clauses)) if self.header.is_none() => { self.header = Some(Header{vars, clauses, at}); },
+                _ => self.tolerate(Error::BadHeader{at})?
+            }
+            return Ok(());
+        }
+        for (column, word) in words
+        {
+            let at = Position::new(self.line, column);
+            let value: isize = match word.parse()
+            {
+                Ok(value) => value,
+                Err(_) => {
+                    self.tolerate(Error::BadLiteral{at, token: word.to_string()})?;
+                    continue;
+                }
+            };
+            //reported once, on the first literal
+            if self.header.is_none() && self.clauses == 0 && self.pending.is_empty() { self.tolerate(Error::HeaderMissing{at})?; }
+            if self.pending.is_empty() { self.start = at; }
+            if value != 0
+            {
+                self.pending.push(value);
+            }
+            else if self.pending.is_empty()
+            {
+                if self.header.is_none_or(|h| self.clauses < h.clauses)
+                {
+                    self.tolerate(Error::EmptyClause{at})?;
+                    //the clause makes the formula unsatisfiable, so it is kept
+                    self.ready.push_back((Clause::empty(), at));
+                    self.clauses += 1;
+                }
+            }
+            else
+            {
+                self.ready.push_back((Clause::new_cn(&self.pending), self.start));
+                self.pending.clear();
+                self.clauses += 1;
+            }
+        }
+        Ok(())
+    }
+
+    ///checks that the input did not end in the middle of a clause or before the header
+    fn finish(&mut self) -> Result<(), Error>
+    {
+        if !self.pending.is_empty()
+        {
+            self.tolerate(Error::UnterminatedClause{at: self.start})?;
+            //the literals read are kept as a clause
+            self.ready.push_back((Clause::new_cn(&self.pending), self.start));
+            self.pending.clear();
+            self.clauses += 1;
+        }
+        if self.header.is_none() && self.clauses == 0 { self.tolerate(Error::HeaderMissing{at: Position::new(self.line.max(1), 1)})?; }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for Parser<R>
+{
+    type Item = Result<(Clause, Position), Error>;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        loop
+        {
+            if let Some(clause) = self.ready.pop_front() { return Some(Ok(clause)); }
+            if let Some(e) = self.error.take() { return Some(Err(e)); }
+            if self.finished { return None; }
+            let result = match self.read_line()
+            {
+                //a line with the end marker sets finished
+                Ok(true) if !self.finished => continue,
+                Ok(_) => self.finish(),
+                Err(e) => Err(e)
+            };
+            self.finished = true;
+            self.error = result.err();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{clause::Clause, error::{Error, Position}};
+    use super::{Parser, ParseMode};
+
+    fn parse(input: &str) -> Result<Vec<Clause>, Error>
+    {
+        Parser::new(input.as_bytes()).map(|c| c.map(|(c, _)| c)).collect()
+    }
+
+    #[test]
+    fn layout()
+    {
+        let clauses = parse("c comment\np\tcnf 4  3\n1 -2\n\t3 0 -4 0 2\n\n-3 0\n%\n0\n").unwrap();
+        assert_eq!(clauses, vec![Clause::new_c3(1,-2,3), Clause::new_c1(-4), Clause::new_c2(2,-3)]);
+
+        let mut parser = Parser::new("c first  one \np cnf 1 1\nc\n1 0\n  cx\n".as_bytes());
+        assert_eq!(parser.by_ref().count(), 1);
+        assert_eq!(parser.comments(), &["first  one", "", "x"]);
+
+        let mut parser = Parser::new("p cnf 2 2\r\n1 2 0\r\n  -1\r\n-2 0\r\n".as_bytes());
+        assert_eq!(parser.next().unwrap().unwrap(), (Clause::new_c2(1,2), Position::new(2, 1)));
+        assert_eq!(parser.next().unwrap().unwrap(), (Clause::new_c2(-1,-2), Position::new(3, 3)));
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn errors()
+    {
+        assert!(matches!(parse("p cnf 2 1\n1 2\n"), Err(Error::UnterminatedClause{at: Position{line: 2, column: 1}})));
+        assert!(matches!(parse("1 2 0\n"), Err(Error::HeaderMissing{at: Position{line: 1, column: 1}})));
+        assert!(matches!(parse(""), Err(Error::HeaderMissing{..})));
+        assert!(matches!(parse("p dnf 2 1\n"), Err(Error::BadHeader{..})));
+        assert!(matches!(parse("p cnf 2 1\np cnf 2 1\n"), Err(Error::BadHeader{at: Position{line: 2, column: 1}})));
+        assert!(matches!(parse("p cnf 2 2\n1 0 0\n"), Err(Error::EmptyClause{at: Position{line: 2, column: 5}})));
+        assert_eq!(parse("p cnf 2 2\n1 0 2 0\n0\n").unwrap().len(), 2);
+
+        //the clauses before the error are returned
+        let mut parser = Parser::new("p cnf 2 2\n1 0 -x 0\n".as_bytes());
+        assert!(parser.next().unwrap().is_ok());
+        assert!(matches!(parser.next(), Some(Err(Error::BadLiteral{..}))));
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn lenient()
+    {
+        let input = "1 2 0\np cnf 3 3\np cnf 3 3\n-1 x 3 0\n0\n2 -3";
+        let mut parser = Parser::with_mode(input.as_bytes(), ParseMode::Lenient);
+        let clauses: Vec<Clause> = parser.by_ref().map(|c| c.unwrap().0).collect();
+        assert_eq!(clauses, vec![Clause::new_c2(1,2), Clause::new_c2(-1,3), Clause::empty(), Clause::new_c2(2,-3)]);
+        let warnings = parser.warnings();
+        assert_eq!(warnings.len(), 5);
+        assert!(matches!(warnings[0], Error::HeaderMissing{at: Position{line: 1, column: 1}}));
+        assert!(matches!(warnings[1], Error::BadHeader{at: Position{line: 3, column: 1}}));
+        assert!(matches!(warnings[2], Error::BadLiteral{at: Position{line: 4, column: 4}, ..}));
+        assert!(matches!(warnings[3], Error::EmptyClause{at: Position{line: 5, column: 1}}));
+        assert!(matches!(warnings[4], Error::UnterminatedClause{at: Position{line: 6, column: 1}}));
+
+        //strict mode stops at the first deviation
+        assert!(matches!(parse(input), Err(Error::HeaderMissing{..})));
+    }
+}
@@ -0,0 +1,167 @@
+use std::{fs::File, io::{self, BufWriter, Write}};
+use crate::{clause::Literal, error::Error};
+
+/*
+Writer of DRAT proofs, the certificates of unsatisfiability read by checkers such as drat-trim.
+A proof is the list of clauses derived by the solver, each one implied by the formula and by the clauses before it,
+ending with the empty clause. Clauses can also be deleted to speed up the checking.
+The text format writes one clause per line as in dimacs, deletions start with "d".
+The binary format starts every clause with 'a' or 'd' and writes the literals as variable length integers, terminated by 0.
+*/
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProofFormat
+{
+    #[default]
+    Text,
+    Binary
+}
+
+pub struct Proof
+{
+    writer: Box<dyn Write + Send>,
+    format: ProofFormat,
+    additions: u64,
+    deletions: u64,
+    //the first error met while writing, searches do not stop for it and it is reported by finish
+    error: Option<io::Error>
+}
+
+impl Proof
+{
+    ///Creates a proof written to any writer, e.g. a file or stdout
+    /// # Example
+    /// ```
+    /// use colombini_sat::{drat::{Proof, ProofFormat}, clause::Literal};
+    ///
+    /// let mut proof = Proof::new(std::io::sink(), ProofFormat::Binary);
+    /// proof.add(&[Literal::from_isize(-3)]);
+    /// proof.add(&[]);
+    /// assert_eq!(proof.additions(), 2);
+    /// proof.finish().unwrap();
+    /// ```
+    pub fn new<W: Write + Send + 'static>(writer: W, format: ProofFormat) -> Proof
+    {
+        Proof{writer: Box::new(writer), format, additions: 0, deletions: 0, error: None}
+    }
+
+    ///Creates a proof written to a file, replacing it if it exists
+    pub fn create(path: &str, format: ProofFormat) -> Result<Proof, Error>
+    {
+        Ok(Proof::new(BufWriter::new(File::create(path)?), format))
+    }
+
+    ///Logs a clause implied by the formula and by the clauses logged before it
+    pub fn add(&mut self, literals: &[Literal])
+    {
+        self.additions += 1;
+        self.write(b'a', literals);
+    }
+
+    ///Logs that a clause is no longer used
+    pub fn delete(&mut self, literals: &[Literal])
+    {
+        self.deletions += 1;
+        self.write(b'd', literals);
+    }
+
+    ///returns the number of clauses added, the empty clause included
+    pub fn additions(&self) -> u64
+    {
+        self.additions
+    }
+
+    pub fn deletions(&self) -> u64
+    {
+        self.deletions
+    }
+
+    ///Flushes the proof
+    /// # Returns
+    /// * The first error met while writing the proof
+    pub fn finish(mut self) -> io::Result<()>
+    {
+        if let Some(e) = self.error.take() { return Err(e); }
+        self.writer.flush()
+    }
+
+    fn write(&mut self, kind: u8, literals: &[Literal])
+    {
+        if self.error.is_some() { return; }
+        let mut bytes: Vec<u8> = Vec::with_capacity(2 + 4*literals.len());
+        match self.format
+        {
+            ProofFormat::Text => {
+                if kind == b'd' { bytes.extend_from_slice(b"d "); }
+                for lit in literals
+                {
+                    bytes.extend_from_slice(lit.as_isize().to_string().as_bytes());
+                    bytes.push(b' ');
+                }
+                bytes.extend_from_slice(b"0\n");
+            },
+            ProofFormat::Binary => {
+                bytes.push(kind);
+                for lit in literals
+                {
+                    //2 * variable for positive literals, plus one for negative ones
+                    let mut code = 2*(lit.index as u64 + 1) + !lit.value as u64;
+                    while code > 127
+                    {
+                        bytes.push((code & 127) as u8 | 128);
+                        code >>= 7;
+                    }
+                    bytes.push(code as u8);
+                }
+                bytes.push(0);
+            }
+        }
+        if let Err(e) = self.writer.write_all(&bytes) { self.error = Some(e); }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{io::{self, Write}, sync::{Arc, Mutex}};
+    use crate::clause::Clause;
+    use super::{Proof, ProofFormat};
+
+    //a writer whose output can be read after the proof takes ownership of it
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared
+    {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+        {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()>
+        {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn formats()
+    {
+        let out = Shared::default();
+        let mut proof = Proof::new(out.clone(), ProofFormat::Text);
+        proof.add(&Clause::new_cn(&[1, -2]).literals);
+        proof.delete(&Clause::new_cn(&[3]).literals);
+        proof.add(&[]);
+        assert_eq!((proof.additions(), proof.deletions()), (2, 1));
+        proof.finish().unwrap();
+        assert_eq!(String::from_utf8(out.0.lock().unwrap().clone()).unwrap(), "1 -2 0\nd 3 0\n0\n");
+
+        //the examples of the drat-trim documentation
+        let out = Shared::default();
+        let mut proof = Proof::new(out.clone(), ProofFormat::Binary);
+        proof.add(&Clause::new_cn(&[-63]).literals);
+        proof.delete(&Clause::new_cn(&[129, -8193]).literals);
+        proof.finish().unwrap();
+        assert_eq!(*out.0.lock().unwrap(), vec![b'a', 127, 0, b'd', 130, 2, 131, 128, 1, 0]);
+    }
+}
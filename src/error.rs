@@ -1,25 +1,157 @@
-use std;
+use std::{fmt, io};
+
+use crate::clause::Clause;
+
+///A position in a dimacs file, lines and columns start from 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position
+{
+    pub line: usize,
+    pub column: usize
+}
+
+impl Position
+{
+    pub fn new(line: usize, column: usize) -> Position
+    {
+        Position{line, column}
+    }
+}
+
+impl fmt::Display for Position
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
 
 #[derive(Debug)]
-pub struct Error
+pub enum Error
 {
-    message: String
+    ///a clause was found before the "p cnf" line, or the file has no clauses nor header
+    HeaderMissing{at: Position},
+    ///the "p" line is not in the form "p cnf <variables> <clauses>"
+    BadHeader{at: Position},
+    ///the formula does not match the sizes declared in the header
+    HeaderMismatch{at: Position, declared_vars: usize, declared_clauses: usize, vars: usize, clauses: usize},
+    ///a token of a clause is not a literal
+    BadLiteral{at: Position, token: String},
+    ///a clause has no literals
+    EmptyClause{at: Position},
+    ///a clause is not terminated by 0
+    UnterminatedClause{at: Position},
+    ///a clause has more literals than allowed, the position is known only for clauses read from a file
+    ClauseTooWide{at: Option<Position>, width: usize, max: usize},
+    Io{at: Option<Position>, source: io::Error},
+    ///the formula was proven unsatisfiable
+    Unsatisfiable{reason: &'static str},
+    ///a step of a proof of unsatisfiability does not follow from the clauses before it, steps start from 1
+    ProofRejected{step: usize, reason: String},
+    ///a literal given to a builder is 0 or uses a variable that was not created
+    InvalidLiteral{literal: isize, vars: usize},
+    ///a search returned an assignment that falsifies a clause of the formula, this is a bug of the solver
+    ModelFalsified{index: usize, clause: Clause},
+    ///the search was stopped by its terminate callback before it found an answer
+    Terminated,
+    Other{message: String}
 }
 
 impl Error
 {
     pub fn new(message: &str) -> Error
     {
-        Error{message: message.to_string()}
+        Error::Other{message: message.to_string()}
+    }
+
+    ///returns the position in the file where the error was found, if any
+    pub fn position(&self) -> Option<Position>
+    {
+        match self
+        {
+            Error::HeaderMissing{at} | Error::BadHeader{at} | Error::HeaderMismatch{at, ..}
+            | Error::BadLiteral{at, ..} | Error::EmptyClause{at} | Error::UnterminatedClause{at} => Some(*at),
+            Error::ClauseTooWide{at, ..} | Error::Io{at, ..} => *at,
+            Error::Unsatisfiable{..} | Error::ProofRejected{..} | Error::ModelFalsified{..} | Error::Terminated | Error::InvalidLiteral{..} | Error::Other{..} => None
+        }
+    }
+
+    ///Moves the error to the given line of a file, used when a line is parsed on its own
+    pub fn at_line(mut self, line: usize) -> Error
+    {
+        match &mut self
+        {
+            Error::HeaderMissing{at} | Error::BadHeader{at} | Error::HeaderMismatch{at, ..}
+            | Error::BadLiteral{at, ..} | Error::EmptyClause{at} | Error::UnterminatedClause{at} => at.line = line,
+            Error::ClauseTooWide{at: Some(at), ..} | Error::Io{at: Some(at), ..} => at.line = line,
+            _ => {}
+        }
+        self
     }
 }
 
-impl std::fmt::Display for Error
+impl fmt::Display for Error
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
     {
-        write!(f, "{}", self.message)
+        if let Some(at) = self.position()
+        {
+            write!(f, "{at}: ")?;
+        }
+        match self
+        {
+            Error::HeaderMissing{..} => write!(f, "missing \"p cnf\" header"),
+            Error::BadHeader{..} => write!(f, "the header must be \"p cnf <variables> <clauses>\""),
+            Error::HeaderMismatch{declared_vars, declared_clauses, vars, clauses, ..} => write!(f,
+                "the header declares {declared_vars} variables and {declared_clauses} clauses, found {vars} variables and {clauses} clauses"),
+            Error::BadLiteral{token, ..} => write!(f, "\"{token}\" is not a literal"),
+            Error::EmptyClause{..} => write!(f, "empty clause"),
+            Error::UnterminatedClause{..} => write!(f, "clause not terminated by 0"),
+            Error::ClauseTooWide{width, max, ..} => write!(f, "clause with {width} literals, at most {max} are allowed"),
+            Error::Io{source, ..} => write!(f, "{source}"),
+            Error::Unsatisfiable{reason} => write!(f, "Not satisfiable, {reason}"),
+            Error::ProofRejected{step, reason} => write!(f, "proof step {step}: {reason}"),
+            Error::InvalidLiteral{literal: 0, ..} => write!(f, "0 is not a literal"),
+            Error::InvalidLiteral{literal, vars} => write!(f, "the literal {literal} uses a variable that was not created, there are {vars}"),
+            Error::ModelFalsified{index, clause} => write!(f, "internal error: the model falsifies the clause {index} {clause}"),
+            Error::Terminated => write!(f, "the search was terminated"),
+            Error::Other{message} => write!(f, "{message}")
+        }
     }
 }
 
-impl std::error::Error for Error {}
\ No newline at end of file
+impl std::error::Error for Error
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+    {
+        match self
+        {
+            Error::Io{source, ..} => Some(source),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for Error
+{
+    fn from(source: io::Error) -> Error
+    {
+        Error::Io{at: None, source}
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{Error, Position};
+
+    #[test]
+    fn display()
+    {
+        let e = Error::BadLiteral{at: Position::new(1, 4), token: "x".to_string()}.at_line(7);
+        assert_eq!(e.position(), Some(Position::new(7, 4)));
+        assert_eq!(e.to_string(), "line 7, column 4: \"x\" is not a literal");
+        let e = Error::ClauseTooWide{at: None, width: 3, max: 2};
+        assert_eq!(e.at_line(2).to_string(), "clause with 3 literals, at most 2 are allowed");
+    }
+}
@@ -0,0 +1,357 @@
+use std::str::FromStr;
+use crate::{clause::Literal, error::Error};
+
+/*
+Choice of the variable to branch on when a lookahead round forces no literal.
+A heuristic sees the clauses of the formula that the current assignments do not satisfy, whose false literals it skips,
+and is told about the conflicts and about the implications found by every probe, so it can keep its own scores. Ties between the best variables are broken
+by a seeded generator: two runs with the same seed make the same choices, different seeds explore different trees.
+*/
+
+pub trait BranchingHeuristic
+{
+    ///Picks one of the variables, the search falls back to the first one if another is returned
+    /// # Arguments
+    /// * `clauses` - The clauses of the formula that the assignments do not satisfy, in the order of the formula
+    /// * `variables` - The unassigned variables of those clauses, in increasing order
+    /// * `assignments` - The value of every variable, the assigned literals of the clauses are false
+    /// # Returns
+    /// * The literal to try first, None if there are no clauses
+    fn pick(&mut self, clauses: &[&[Literal]], variables: &[usize], assignments: &[Option<bool>]) -> Option<Literal>;
+
+    ///Called with the literals of a clause falsified by the search
+    fn on_conflict(&mut self, _clause: &[Literal]) {}
+
+    ///Called after both values of a variable were probed, with the number of literals implied by each of them
+    fn on_probe(&mut self, _index: usize, _implied_true: usize, _implied_false: usize) {}
+}
+
+///The heuristics provided, to be created with a seed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Heuristic
+{
+    ///variable state independent decaying sum: the variables of recent conflicts first
+    Vsids,
+    ///the literal in the most clauses, clauses with fewer unassigned literals weighing exponentially more
+    JeroslowWang,
+    ///the variable with the most occurrences in the shortest clauses
+    Moms,
+    ///the variable whose probes implied the most literals for both values
+    #[default]
+    Lookahead
+}
+
+impl Heuristic
+{
+    pub fn create(&self, seed: u64) -> Box<dyn BranchingHeuristic + Send>
+    {
+        match self
+        {
+            Heuristic::Vsids => Box::new(Vsids::new(seed)),
+            Heuristic::JeroslowWang => Box::new(JeroslowWang::new(seed)),
+            Heuristic::Moms => Box::new(Moms::new(seed)),
+            Heuristic::Lookahead => Box::new(LookaheadScore::new(seed))
+        }
+    }
+}
+
+///Parses the name of a heuristic: vsids, jw (or jeroslow-wang), moms or lookahead
+impl FromStr for Heuristic
+{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Heuristic, Error>
+    {
+        match s
+        {
+            "vsids" => Ok(Heuristic::Vsids),
+            "jw" | "jeroslow-wang" => Ok(Heuristic::JeroslowWang),
+            "moms" => Ok(Heuristic::Moms),
+            "lookahead" => Ok(Heuristic::Lookahead),
+            other => Err(Error::new(&format!("unknown heuristic \"{other}\"")))
+        }
+    }
+}
+
+///SplitMix64, a small generator whose whole sequence is fixed by the seed
+/// # Example
+/// ```
+/// use colombini_sat::heuristics::Rng;
+///
+/// let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// assert!(a.below(10) < 10);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Rng
+{
+    state: u64
+}
+
+impl Rng
+{
+    pub fn new(seed: u64) -> Rng
+    {
+        Rng{state: seed}
+    }
+
+    pub fn next_u64(&mut self) -> u64
+    {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    ///returns a number in 0..n, n must not be 0
+    pub fn below(&mut self, n: usize) -> usize
+    {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+///returns the unassigned literals of a clause
+fn free<'a>(clause: &'a [Literal], assignments: &'a [Option<bool>]) -> impl Iterator<Item = &'a Literal> + 'a
+{
+    clause.iter().filter(|l| assignments[l.index].is_none())
+}
+
+///returns the variables with the highest score, one of them chosen by the generator
+fn best<F: Fn(usize) -> f64>(variables: &[usize], rng: &mut Rng, score: F) -> Option<usize>
+{
+    let mut top: Vec<usize> = Vec::new();
+    let mut max = f64::NEG_INFINITY;
+    for index in variables.iter().copied()
+    {
+        let s = score(index);
+        if s > max
+        {
+            max = s;
+            top.clear();
+        }
+        if s == max { top.push(index); }
+    }
+    if top.is_empty() { return None; }
+    Some(top[rng.below(top.len())])
+}
+
+fn literal(index: usize, value: bool) -> Literal
+{
+    Literal{index, value, implicated: false, assigned: false}
+}
+
+///for every literal, indexed by 2*variable + value, the sum of the weights of the clauses it is unassigned in,
+///given by their number of unassigned literals
+fn literal_weights<F: Fn(usize) -> f64>(clauses: &[&[Literal]], assignments: &[Option<bool>], weight: F) -> Vec<f64>
+{
+    let mut weights = vec![0.0; 2*assignments.len()];
+    for clause in clauses
+    {
+        let w = weight(free(clause, assignments).count());
+        for lit in free(clause, assignments)
+        {
+            weights[2*lit.index + lit.value as usize] += w;
+        }
+    }
+    weights
+}
+
+pub struct Vsids
+{
+    activity: Vec<f64>,
+    bump: f64,
+    rng: Rng
+}
+
+const VSIDS_DECAY: f64 = 0.95;
+
+impl Vsids
+{
+    pub fn new(seed: u64) -> Vsids
+    {
+        Vsids{activity: Vec::new(), bump: 1.0, rng: Rng::new(seed)}
+    }
+}
+
+impl BranchingHeuristic for Vsids
+{
+    fn pick(&mut self, _clauses: &[&[Literal]], variables: &[usize], _assignments: &[Option<bool>]) -> Option<Literal>
+    {
+        let activity = &self.activity;
+        best(variables, &mut self.rng, |i| activity.get(i).copied().unwrap_or(0.0)).map(|i| literal(i, true))
+    }
+
+    fn on_conflict(&mut self, clause: &[Literal])
+    {
+        for lit in clause
+        {
+            if self.activity.len() <= lit.index { self.activity.resize(lit.index + 1, 0.0); }
+            self.activity[lit.index] += self.bump;
+        }
+        self.bump /= VSIDS_DECAY;
+        if self.bump > 1e100
+        {
+            for a in self.activity.iter_mut()
+            {
+                *a *= 1e-100;
+            }
+            self.bump *= 1e-100;
+        }
+    }
+}
+
+pub struct JeroslowWang
+{
+    rng: Rng
+}
+
+impl JeroslowWang
+{
+    pub fn new(seed: u64) -> JeroslowWang
+    {
+        JeroslowWang{rng: Rng::new(seed)}
+    }
+}
+
+impl BranchingHeuristic for JeroslowWang
+{
+    fn pick(&mut self, clauses: &[&[Literal]], variables: &[usize], assignments: &[Option<bool>]) -> Option<Literal>
+    {
+        //two-sided: the variable with the highest sum, then its literal with the highest weight
+        let weights = literal_weights(clauses, assignments, |free| 0.5f64.powi(free as i32));
+        let index = best(variables, &mut self.rng, |i| weights[2*i] + weights[2*i + 1])?;
+        Some(literal(index, weights[2*index + 1] >= weights[2*index]))
+    }
+}
+
+pub struct Moms
+{
+    rng: Rng
+}
+
+impl Moms
+{
+    pub fn new(seed: u64) -> Moms
+    {
+        Moms{rng: Rng::new(seed)}
+    }
+}
+
+//the weight of the occurrences over their balance between the two values
+const MOMS_K: f64 = 1024.0;
+
+impl BranchingHeuristic for Moms
+{
+    fn pick(&mut self, clauses: &[&[Literal]], variables: &[usize], assignments: &[Option<bool>]) -> Option<Literal>
+    {
+        let shortest = clauses.iter().map(|c| free(c, assignments).count()).filter(|free| *free > 0).min()?;
+        let counts = literal_weights(clauses, assignments, |free| if free == shortest { 1.0 } else { 0.0 });
+        let index = best(variables, &mut self.rng, |i| {
+            let (f, t) = (counts[2*i], counts[2*i + 1]);
+            (t + f)*MOMS_K + t*f
+        })?;
+        Some(literal(index, counts[2*index + 1] >= counts[2*index]))
+    }
+}
+
+pub struct LookaheadScore
+{
+    //the literals implied by the last probes of every variable, for true and for false
+    implied: Vec<(usize, usize)>,
+    rng: Rng
+}
+
+impl LookaheadScore
+{
+    pub fn new(seed: u64) -> LookaheadScore
+    {
+        LookaheadScore{implied: Vec::new(), rng: Rng::new(seed)}
+    }
+}
+
+impl BranchingHeuristic for LookaheadScore
+{
+    fn pick(&mut self, _clauses: &[&[Literal]], variables: &[usize], _assignments: &[Option<bool>]) -> Option<Literal>
+    {
+        //the product favours the variables that shrink the formula in both branches
+        let implied = &self.implied;
+        let index = best(variables, &mut self.rng, |i| {
+            let (t, f) = implied.get(i).copied().unwrap_or((0, 0));
+            ((t + 1)*(f + 1)) as f64
+        })?;
+        let (t, f) = self.implied.get(index).copied().unwrap_or((0, 0));
+        //the branch that implies less is tried first, it is the one more likely to be satisfiable
+        Some(literal(index, t <= f))
+    }
+
+    fn on_probe(&mut self, index: usize, implied_true: usize, implied_false: usize)
+    {
+        if self.implied.len() <= index { self.implied.resize(index + 1, (0, 0)); }
+        self.implied[index] = (implied_true, implied_false);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{phi::Phi, clause::{Clause, Literal}};
+    use super::{BranchingHeuristic, Heuristic, JeroslowWang, Moms, Vsids, LookaheadScore};
+
+    fn phi() -> Phi
+    {
+        Phi::from_clauses(vec![
+            Clause::new_c3(1,2,3), Clause::new_c2(-2,4), Clause::new_c2(-2,-4), Clause::new_c3(-1,3,5), Clause::new_c3(2,-3,5)
+        ])
+    }
+
+    ///passes the clauses of phi that the assignments do not satisfy and their variables, as the lookahead does
+    fn choose(heuristic: &mut dyn BranchingHeuristic, phi: &Phi, assignments: &[Option<bool>]) -> Option<Literal>
+    {
+        let value = |l: &Literal| assignments[l.index].map(|v| v == l.value);
+        let clauses: Vec<&[Literal]> = phi.clauses().iter().map(|c| c.literals.as_slice()).filter(|c| !c.iter().any(|l| value(l) == Some(true))).collect();
+        let mut variables: Vec<usize> = clauses.iter().flat_map(|c| c.iter()).filter(|l| value(l).is_none()).map(|l| l.index).collect();
+        variables.sort_unstable();
+        variables.dedup();
+        heuristic.pick(&clauses, &variables, assignments)
+    }
+
+    #[test]
+    fn heuristics()
+    {
+        let phi = phi();
+        let free = vec![None; 5];
+        let pick = |h: &mut dyn BranchingHeuristic| choose(h, &phi, &free).map(|l| l.as_isize());
+
+        //4 is balanced in the shortest clauses, -2 has the most weight in short clauses
+        assert_eq!(pick(&mut Moms::new(0)), Some(4));
+        assert_eq!(pick(&mut JeroslowWang::new(0)), Some(-2));
+
+        let mut vsids = Vsids::new(0);
+        vsids.on_conflict(&[Literal::from_isize(-4), Literal::from_isize(3)]);
+        vsids.on_conflict(&[Literal::from_isize(4)]);
+        assert_eq!(pick(&mut vsids), Some(4));
+
+        let mut lookahead = LookaheadScore::new(0);
+        lookahead.on_probe(2, 3, 1);
+        lookahead.on_probe(0, 6, 0);
+        assert_eq!(pick(&mut lookahead), Some(-3));
+
+        //assigned variables are never picked
+        let mut assignments = free.clone();
+        assignments[2] = Some(false);
+        assert_eq!(choose(&mut lookahead, &phi, &assignments).map(|l| l.index), Some(0));
+        assert_eq!(Moms::new(0).pick(&[], &[], &[]), None);
+        //nor the ones that only appear in satisfied clauses
+        let satisfied = vec![Some(true), Some(false), Some(true), None, Some(true)];
+        assert_eq!(choose(&mut JeroslowWang::new(0), &phi, &satisfied), None);
+
+        //the ties are broken by the seed, the same seed always makes the same choice
+        let choices = |seed: u64| -> Vec<isize> { (0..8).map(|_| pick(&mut *Heuristic::Vsids.create(seed)).unwrap()).collect() };
+        assert_eq!(choices(3), choices(3));
+        let seeds: Vec<isize> = (0..32).map(|seed| pick(&mut *Heuristic::Vsids.create(seed)).unwrap()).collect();
+        assert!(seeds.iter().any(|v| *v != seeds[0]));
+        assert_eq!("jw".parse::<Heuristic>().unwrap(), Heuristic::JeroslowWang);
+        assert!("random".parse::<Heuristic>().is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{clause::{Clause, Literal}, propagation::Propagator};
+
+/*
+Binary implications found by the lookahead: when every model of the 2-SAT formula of a probe gives the same value
+to a variable, the probed literal implies that value in the current branch.
+They are kept as a graph on the literals, every implication with its contrapositive, tagged with the decision level
+it was found at, since it only holds as long as the decisions below it stand; backtracking drops the deeper ones.
+An implication that follows from the stored ones is not added, and adding one removes the implications of the same
+level it makes redundant among the ones leaving its first literal or reaching the second, so the graph stays close
+to its transitive reduction.
+The implications assign the literals implied by the trail and give the 2-SAT formulas of the next probes
+the consequences already known of the probed variable.
+*/
+
+//literals are numbered 2*variable + value, the negation of a literal flips the last bit
+fn code(lit: Literal) -> usize
+{
+    2*lit.index + lit.value as usize
+}
+
+fn literal(code: usize) -> Literal
+{
+    Literal{index: code/2, value: code % 2 == 1, implicated: false, assigned: false}
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Edge
+{
+    from: usize,
+    to: usize,
+    level: usize,
+    removed: bool
+}
+
+///The binary implications of a search
+/// # Example
+/// ```
+/// use colombini_sat::{implications::BinaryImplications, clause::Literal};
+///
+/// let mut implications = BinaryImplications::new();
+/// let l = Literal::from_isize;
+/// assert!(implications.add(l(1), l(2), 0));
+/// assert!(implications.add(l(2), l(3), 0));
+/// //already implied through x2
+/// assert!(!implications.add(l(1), l(3), 0));
+/// assert!(implications.implies(l(-3), l(-1)));
+/// assert_eq!(implications.len(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BinaryImplications
+{
+    //every edge in the order it was added, each one followed by its contrapositive
+    edges: Vec<Edge>,
+    //for every literal, the edges leaving it in the order they were added, by index in edges
+    out: Vec<Vec<usize>>,
+    //edges not removed
+    live: usize,
+    //position in the trail of the next literal whose implications are assigned
+    propagated: usize,
+    //for every literal, the number of the last search that reached it, so the marks are never cleared
+    marks: Vec<usize>,
+    search: usize
+}
+
+impl BinaryImplications
+{
+    pub fn new() -> BinaryImplications
+    {
+        BinaryImplications::default()
+    }
+
+    ///returns the number of implications stored, not counting the contrapositives
+    pub fn len(&self) -> usize
+    {
+        self.live/2
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.live == 0
+    }
+
+    ///Marks the literals reached from the literal, itself included, stopping early if target is reached
+    /// # Returns
+    /// * The codes of the literals reached
+    fn reachable(&mut self, from: usize, target: Option<usize>) -> Vec<usize>
+    {
+        self.search += 1;
+        if self.marks.len() < self.out.len().max(from + 1) { self.marks.resize(self.out.len().max(from + 1), 0); }
+        let mut reached = vec![from];
+        self.marks[from] = self.search;
+        let mut next = 0;
+        while next < reached.len() && target.is_none_or(|t| self.marks.get(t) != Some(&self.search))
+        {
+            let lit = reached[next];
+            next += 1;
+            for id in self.out.get(lit).into_iter().flatten()
+            {
+                let to = self.edges[*id].to;
+                if self.marks[to] != self.search
+                {
+                    self.marks[to] = self.search;
+                    reached.push(to);
+                }
+            }
+        }
+        reached
+    }
+
+    ///returns true if the literal was reached by the last search
+    fn reached(&self, lit: usize) -> bool
+    {
+        self.marks.get(lit) == Some(&self.search)
+    }
+
+    ///returns true if from implies to through the stored implications, every literal implies itself
+    pub fn implies(&mut self, from: Literal, to: Literal) -> bool
+    {
+        self.reachable(code(from), Some(code(to)));
+        self.reached(code(to))
+    }
+
+    ///returns the literals implied by the literal, in ascending order of variable
+    pub fn implied(&mut self, from: Literal) -> Vec<Literal>
+    {
+        let mut reached = self.reachable(code(from), None);
+        reached.sort_unstable();
+        reached.into_iter().filter(|to| *to != code(from)).map(literal).collect()
+    }
+
+    ///Stores an implication with its contrapositive
+    /// # Arguments
+    /// * `from` - The literal that implies the other, of a different variable
+    /// * `to` - The implied literal
+    /// * `level` - The decision level the implication holds from
+    /// # Returns
+    /// * false if the implication already followed from the stored ones, it is then not added
+    pub fn add(&mut self, from: Literal, to: Literal, level: usize) -> bool
+    {
+        if from.index == to.index || self.implies(from, to) { return false; }
+        let (from, to) = (code(from), code(to));
+        //the implications of this level leaving from that are now implied through to, and the other way for the
+        //contrapositive; the older ones are kept since they outlive the new one when the search backtracks
+        self.reachable(to, None);
+        let redundant: Vec<usize> = self.leaving(from, level).filter(|t| self.reached(*t)).collect();
+        for t in redundant { self.remove(from, t); }
+        self.reachable(from ^ 1, None);
+        let redundant: Vec<usize> = self.leaving(to ^ 1, level).filter(|t| self.reached(*t)).collect();
+        for t in redundant { self.remove(to ^ 1, t); }
+
+        self.push(from, to, level);
+        self.push(to ^ 1, from ^ 1, level);
+        true
+    }
+
+    ///returns the literals reached by the edges of the level leaving a literal
+    fn leaving(&self, from: usize, level: usize) -> impl Iterator<Item = usize> + '_
+    {
+        self.out.get(from).into_iter().flatten()
+            .map(|id| self.edges[*id])
+            .filter(move |edge| edge.level == level)
+            .map(|edge| edge.to)
+    }
+
+    fn push(&mut self, from: usize, to: usize, level: usize)
+    {
+        if self.out.len() <= from.max(to) { self.out.resize((from.max(to)/2 + 1)*2, Vec::new()); }
+        self.out[from].push(self.edges.len());
+        self.edges.push(Edge{from, to, level, removed: false});
+        self.live += 1;
+    }
+
+    ///removes an edge and its contrapositive
+    fn remove(&mut self, from: usize, to: usize)
+    {
+        for (from, to) in [(from, to), (to ^ 1, from ^ 1)]
+        {
+            let edges = &mut self.edges;
+            self.out[from].retain(|id| {
+                let found = edges[*id].to == to;
+                if found { edges[*id].removed = true; }
+                !found
+            });
+            self.live -= 1;
+        }
+    }
+
+    ///returns the clauses equivalent to the implications of both literals of a variable
+    pub fn clauses(&mut self, index: usize) -> Vec<Clause>
+    {
+        let mut clauses = Vec::new();
+        for value in [true, false]
+        {
+            let from = Literal{index, value, implicated: false, assigned: false};
+            clauses.extend(self.implied(from).into_iter().map(|to| Clause::new(vec![from.negated(), to])));
+        }
+        clauses
+    }
+
+    ///Assigns the literals implied by the literals of the trail, the assigned ones have no reason
+    /// # Returns
+    /// * The number of literals assigned, or the implied literal that is already false
+    pub fn propagate(&mut self, propagator: &mut Propagator) -> Result<usize, Literal>
+    {
+        let mut assigned = 0;
+        while self.propagated < propagator.trail().len()
+        {
+            let from = code(propagator.trail()[self.propagated]);
+            self.propagated += 1;
+            for id in self.out.get(from).into_iter().flatten()
+            {
+                let to = literal(self.edges[*id].to);
+                match propagator.value(to.index)
+                {
+                    None => {
+                        propagator.assign(to, None);
+                        assigned += 1;
+                    },
+                    Some(value) if value != to.value => return Err(to),
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(assigned)
+    }
+
+    ///Drops the implications found above the decision level of the propagator, to be called after it backtracked
+    pub fn backtrack(&mut self, propagator: &Propagator)
+    {
+        let level = propagator.decision_level();
+        while let Some(edge) = self.edges.last().copied()
+        {
+            if edge.level <= level { break; }
+            self.edges.pop();
+            if !edge.removed
+            {
+                //the edges are popped in the order opposite to the one they were added in
+                self.out[edge.from].pop();
+                self.live -= 1;
+            }
+        }
+        self.propagated = self.propagated.min(propagator.trail().len());
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{phi::Phi, clause::Literal, propagation::Propagator};
+    use super::BinaryImplications;
+
+    #[test]
+    fn implications()
+    {
+        let l = Literal::from_isize;
+        let mut propagator = Propagator::new(&Phi::new(), 8);
+        let mut implications = BinaryImplications::new();
+        assert!(implications.add(l(1), l(2), 0));
+        assert!(!implications.add(l(1), l(2), 0));
+        assert!(implications.add(l(2), l(3), 0));
+        assert!(!implications.add(l(-3), l(-1), 0));
+        assert!(!implications.add(l(1), l(-1), 0));
+
+        //x4 -> x6 is made redundant by x5 -> x6, x2 -> x3 is kept since it holds at level 0
+        propagator.decide(l(4));
+        assert!(implications.add(l(4), l(6), 1));
+        assert!(implications.add(l(4), l(5), 1));
+        assert!(implications.add(l(5), l(6), 1));
+        assert!(implications.add(l(2), l(8), 1));
+        assert!(implications.add(l(8), l(3), 1));
+        assert_eq!(implications.len(), 6);
+        assert!(implications.implies(l(4), l(6)));
+        assert_eq!(implications.propagate(&mut propagator), Ok(2));
+        assert_eq!((propagator.value(4), propagator.value(5)), (Some(true), Some(true)));
+
+        propagator.backtrack(0);
+        implications.backtrack(&propagator);
+        assert_eq!(implications.len(), 2);
+        assert!(implications.implies(l(2), l(3)));
+        assert!(!implications.implies(l(4), l(5)));
+        let clauses: Vec<Vec<isize>> = implications.clauses(1).iter().map(|c| c.literals.iter().map(|l| l.as_isize()).collect()).collect();
+        assert_eq!(clauses, vec![vec![-2, 3], vec![2, -1]]);
+
+        //x1 implies x2, -x3 implies -x2
+        propagator.decide(l(1));
+        propagator.decide(l(-3));
+        assert_eq!(implications.propagate(&mut propagator).map_err(|lit| lit.as_isize()), Err(-2));
+    }
+}
@@ -0,0 +1,201 @@
+use std::ffi::{c_char, c_int, c_void};
+use log::error;
+use crate::{solver::Solver, error::Error};
+
+/*
+C interface of the incremental solver, following the IPASIR conventions of the SAT races
+so that the tools written against include/ipasir.h can link the cdylib without glue code.
+The header is generated by cbindgen from the functions below and their documentation, see cbindgen.toml.
+Clauses are added one literal at a time and closed by 0, assumptions hold for the next solve only.
+ipasir_solve returns 10 for satisfiable formulas and 20 for unsatisfiable ones, 0 when the terminate callback
+stopped the search, which polls it on every conflict, or when the search failed with an internal error.
+*/
+
+const SIGNATURE: &[u8] = concat!("colombini_sat-", env!("CARGO_PKG_VERSION"), "\0").as_bytes();
+
+pub struct Ipasir
+{
+    solver: Solver,
+    //the literals of the clause being added, until its 0
+    clause: Vec<isize>,
+    assumptions: Vec<isize>
+}
+
+///returns the solver behind a handle of the C interface
+/// # Safety
+/// The handle must come from ipasir_init and must not have been released
+unsafe fn handle<'a>(solver: *mut c_void) -> &'a mut Ipasir
+{
+    &mut *(solver as *mut Ipasir)
+}
+
+///returns the name and the version of the solver
+#[no_mangle]
+pub extern "C" fn ipasir_signature() -> *const c_char
+{
+    SIGNATURE.as_ptr() as *const c_char
+}
+
+///Creates a solver with no clauses, it must be freed with ipasir_release
+#[no_mangle]
+pub extern "C" fn ipasir_init() -> *mut c_void
+{
+    Box::into_raw(Box::new(Ipasir{solver: Solver::new(), clause: Vec::new(), assumptions: Vec::new()})) as *mut c_void
+}
+
+///Frees the solver
+/// # Safety
+/// The handle must come from ipasir_init and can not be used after this call
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_release(solver: *mut c_void)
+{
+    if !solver.is_null() { drop(Box::from_raw(solver as *mut Ipasir)); }
+}
+
+///Adds a literal to the clause being built, 0 adds the clause to the formula
+/// # Safety
+/// The handle must come from ipasir_init
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_add(solver: *mut c_void, lit_or_zero: i32)
+{
+    let ipasir = handle(solver);
+    if lit_or_zero != 0
+    {
+        ipasir.clause.push(lit_or_zero as isize);
+    }
+    else
+    {
+        let clause = std::mem::take(&mut ipasir.clause);
+        ipasir.solver.add_clause(&clause);
+    }
+}
+
+///Assumes the literal true for the next call to ipasir_solve only
+/// # Safety
+/// The handle must come from ipasir_init, the literal can not be 0
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_assume(solver: *mut c_void, lit: i32)
+{
+    handle(solver).assumptions.push(lit as isize);
+}
+
+///Solves the formula under the assumptions given since the last call
+/// # Returns
+/// * 10 if the formula is satisfiable, 20 if it is not, 0 if the search was terminated or failed with an internal error
+/// # Safety
+/// The handle must come from ipasir_init
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_solve(solver: *mut c_void) -> c_int
+{
+    let ipasir = handle(solver);
+    let assumptions = std::mem::take(&mut ipasir.assumptions);
+    match ipasir.solver.solve_with_assumptions(&assumptions)
+    {
+        Ok(Some(_)) => 10,
+        Ok(None) => 20,
+        Err(Error::Terminated) => 0,
+        Err(e) => {
+            error!("{e}");
+            0
+        }
+    }
+}
+
+///returns the value of the literal in the model found by the last ipasir_solve that returned 10:
+///the literal if it is true, its negation if it is false and 0 if the variable is not in the formula
+/// # Safety
+/// The handle must come from ipasir_init
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_val(solver: *mut c_void, lit: i32) -> i32
+{
+    match handle(solver).solver.value(lit as isize)
+    {
+        Some(true) => lit,
+        Some(false) => -lit,
+        None => 0
+    }
+}
+
+///returns 1 if the assumption was used to prove the formula unsatisfiable in the last ipasir_solve that returned 20
+/// # Safety
+/// The handle must come from ipasir_init
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_failed(solver: *mut c_void, lit: i32) -> c_int
+{
+    handle(solver).solver.failed(lit as isize) as c_int
+}
+
+///Sets a callback polled by ipasir_solve on every conflict, the search stops and returns 0 when it returns non zero.
+///A null callback removes the previous one
+/// # Safety
+/// The handle must come from ipasir_init, data is passed to the callback as it is
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_set_terminate(solver: *mut c_void, data: *mut c_void, terminate: Option<extern "C" fn(*mut c_void) -> c_int>)
+{
+    let terminate = terminate.map(|terminate| Box::new(move || terminate(data) != 0) as Box<dyn FnMut() -> bool>);
+    handle(solver).solver.set_terminate(terminate);
+}
+
+///Accepted for compatibility, the learned clauses are not exported
+/// # Safety
+/// The handle must come from ipasir_init
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_set_learn(_solver: *mut c_void, _data: *mut c_void, _max_length: c_int, _learn: Option<extern "C" fn(*mut c_void, *mut i32)>)
+{
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::ffi::CStr;
+    use super::*;
+
+    #[test]
+    fn interface()
+    {
+        unsafe
+        {
+            let solver = ipasir_init();
+            for lit in [-1, 2, 0, -2, 3, 0]
+            {
+                ipasir_add(solver, lit);
+            }
+            ipasir_assume(solver, 1);
+            ipasir_assume(solver, -3);
+            assert_eq!(ipasir_solve(solver), 20);
+            assert_eq!((ipasir_failed(solver, 1), ipasir_failed(solver, -3)), (1, 1));
+            //the assumptions only hold for one call
+            assert_eq!(ipasir_solve(solver), 10);
+            ipasir_add(solver, 1);
+            ipasir_add(solver, 0);
+            assert_eq!(ipasir_solve(solver), 10);
+            assert_eq!((ipasir_val(solver, 3), ipasir_val(solver, -2), ipasir_val(solver, 9)), (3, 2, 0));
+            ipasir_release(solver);
+
+            //the callback stops the search at its first conflict, it is polled again by the next calls
+            extern "C" fn polls(data: *mut c_void) -> c_int
+            {
+                let calls = unsafe { &mut *(data as *mut i32) };
+                *calls += 1;
+                (*calls == 1) as c_int
+            }
+            let mut calls = 0;
+            let solver = ipasir_init();
+            for lit in [1, 2, 0, 1, -2, 0, -1, 2, 0, -1, -2, 0]
+            {
+                ipasir_add(solver, lit);
+            }
+            ipasir_set_terminate(solver, &mut calls as *mut i32 as *mut c_void, Some(polls));
+            assert_eq!(ipasir_solve(solver), 0);
+            assert_eq!(ipasir_val(solver, 1), 0);
+            assert_eq!(ipasir_solve(solver), 20);
+            assert_eq!(calls, 2);
+            ipasir_set_terminate(solver, std::ptr::null_mut(), None);
+            assert_eq!(ipasir_solve(solver), 20);
+            assert_eq!(calls, 2);
+            ipasir_release(solver);
+        }
+        let signature = unsafe { CStr::from_ptr(ipasir_signature()) };
+        assert!(signature.to_str().unwrap().starts_with("colombini_sat-"));
+    }
+}
@@ -0,0 +1,250 @@
+use std::{cmp::Reverse, collections::HashMap, str::FromStr};
+use crate::{clause::Literal, propagation::Propagator, drat::Proof, error::Error};
+
+/*
+Clauses learned by the searches: the clauses CDCL derives from its conflicts and the literals forced by the
+lookahead under the current decisions. They are propagated with the clauses of the formula but managed apart,
+every learned clause is tagged with its literal block distance, the number of decision levels among its literals
+when it was learned, and with an activity bumped whenever it takes part in a conflict.
+When there are too many of them the reduction policy deletes the half judged least useful, sparing the clauses
+that are the reason of an assignment and the binary ones, and the limit grows so that long runs stay complete.
+*/
+
+///When and which learned clauses are deleted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReductionPolicy
+{
+    ///learned clauses are never deleted
+    Never,
+    ///beyond limit clauses the half with the most decision levels is deleted, the clauses with at most two levels
+    ///are always kept; the limit then grows by increment
+    Lbd{limit: usize, increment: usize},
+    ///beyond limit clauses the half that took part in the fewest recent conflicts is deleted,
+    ///the limit then grows by increment
+    Activity{limit: usize, increment: usize}
+}
+
+impl Default for ReductionPolicy
+{
+    fn default() -> Self
+    {
+        ReductionPolicy::Lbd{limit: REDUCTION_LIMIT, increment: REDUCTION_INCREMENT}
+    }
+}
+
+///Parses the name of a policy: never, lbd or activity, with the default limits
+impl FromStr for ReductionPolicy
+{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ReductionPolicy, Error>
+    {
+        match s
+        {
+            "never" => Ok(ReductionPolicy::Never),
+            "lbd" => Ok(ReductionPolicy::Lbd{limit: REDUCTION_LIMIT, increment: REDUCTION_INCREMENT}),
+            "activity" => Ok(ReductionPolicy::Activity{limit: REDUCTION_LIMIT, increment: REDUCTION_INCREMENT}),
+            other => Err(Error::new(&format!("unknown reduction policy \"{other}\"")))
+        }
+    }
+}
+
+const REDUCTION_LIMIT: usize = 2000;
+const REDUCTION_INCREMENT: usize = 300;
+const ACTIVITY_DECAY: f64 = 0.999;
+//the clauses with this many decision levels or fewer are kept by the lbd policy
+const GLUE: usize = 2;
+
+///returns the number of decision levels among the literals, the unassigned ones counting as one more level
+pub fn lbd(propagator: &Propagator, literals: &[Literal]) -> usize
+{
+    let mut levels: Vec<Option<usize>> = literals.iter()
+        .map(|l| propagator.value(l.index).map(|_| propagator.level(l.index)))
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+    levels.len()
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Tag
+{
+    lbd: usize,
+    activity: f64
+}
+
+///The learned clauses of a propagator
+/// # Example
+/// ```
+/// use colombini_sat::{learned::{LearnedClauses, ReductionPolicy}, propagation::Propagator, phi::Phi, clause::Literal};
+///
+/// let mut propagator = Propagator::new(&Phi::new(), 3);
+/// let mut learned = LearnedClauses::new(ReductionPolicy::Activity{limit: 1, increment: 1});
+/// for clause in [[1, 2, 3], [-1, 2, 3]]
+/// {
+///     learned.add(&mut propagator, clause.iter().map(|l| Literal::from_isize(*l)).collect(), 3);
+/// }
+/// assert_eq!(learned.reduce(&mut propagator, &mut None), 1);
+/// assert_eq!((learned.len(), propagator.num_clauses()), (1, 1));
+/// ```
+#[derive(Clone, Debug)]
+pub struct LearnedClauses
+{
+    policy: ReductionPolicy,
+    limit: usize,
+    //the tags of the learned clauses, by index in the propagator
+    tags: HashMap<usize, Tag>,
+    bump: f64
+}
+
+impl LearnedClauses
+{
+    pub fn new(policy: ReductionPolicy) -> LearnedClauses
+    {
+        let limit = match policy
+        {
+            ReductionPolicy::Never => usize::MAX,
+            ReductionPolicy::Lbd{limit, ..} | ReductionPolicy::Activity{limit, ..} => limit
+        };
+        LearnedClauses{policy, limit, tags: HashMap::new(), bump: 1.0}
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.tags.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.tags.is_empty()
+    }
+
+    ///returns the literal block distance of a learned clause, None for the clauses of the formula
+    pub fn lbd(&self, index: usize) -> Option<usize>
+    {
+        self.tags.get(&index).map(|tag| tag.lbd)
+    }
+
+    ///Adds a learned clause to the propagator, assigning its first literal if the others are false
+    /// # Arguments
+    /// * `literals` - The clause, implied by the clauses of the propagator
+    /// * `lbd` - Its literal block distance, computed before the search jumped back
+    /// # Returns
+    /// * The index of the clause in the propagator
+    pub fn add(&mut self, propagator: &mut Propagator, literals: Vec<Literal>, lbd: usize) -> usize
+    {
+        let index = propagator.add_implied(literals);
+        self.tags.insert(index, Tag{lbd, activity: self.bump});
+        index
+    }
+
+    ///Bumps the activity of a clause that took part in a conflict, the clauses of the formula are ignored
+    pub fn bump(&mut self, index: usize)
+    {
+        if let Some(tag) = self.tags.get_mut(&index)
+        {
+            tag.activity += self.bump;
+            if tag.activity > 1e100
+            {
+                for tag in self.tags.values_mut()
+                {
+                    tag.activity *= 1e-100;
+                }
+                self.bump *= 1e-100;
+            }
+        }
+    }
+
+    ///Makes the conflicts seen so far weigh less than the next ones, to be called after every conflict
+    pub fn decay(&mut self)
+    {
+        self.bump /= ACTIVITY_DECAY;
+    }
+
+    ///Deletes half of the learned clauses if there are more than the limit of the policy,
+    ///logging the deletions to the proof
+    /// # Returns
+    /// * The number of clauses deleted
+    pub fn reduce(&mut self, propagator: &mut Propagator, proof: &mut Option<&mut Proof>) -> usize
+    {
+        if self.tags.len() <= self.limit { return 0; }
+        let (glue, increment) = match self.policy
+        {
+            ReductionPolicy::Never => return 0,
+            ReductionPolicy::Lbd{increment, ..} => (GLUE, increment),
+            ReductionPolicy::Activity{increment, ..} => (0, increment)
+        };
+        let mut candidates: Vec<(usize, Tag)> = self.tags.iter()
+            .filter(|(index, tag)| tag.lbd > glue && propagator.clause(**index).len() > 2 && !propagator.is_reason(**index))
+            .map(|(index, tag)| (*index, *tag))
+            .collect();
+        //the least useful first, the index makes the order independent of the hashing
+        match self.policy
+        {
+            ReductionPolicy::Lbd{..} => candidates.sort_by(|(i, a), (j, b)| Reverse(a.lbd).cmp(&Reverse(b.lbd)).then(a.activity.total_cmp(&b.activity)).then(i.cmp(j))),
+            _ => candidates.sort_by(|(i, a), (j, b)| a.activity.total_cmp(&b.activity).then(i.cmp(j)))
+        }
+        candidates.truncate(self.tags.len()/2);
+        let deleted: Vec<usize> = candidates.into_iter().map(|(index, _)| index).collect();
+        for index in &deleted
+        {
+            if let Some(proof) = proof { proof.delete(propagator.clause(*index)); }
+            self.tags.remove(index);
+        }
+        propagator.delete_clauses(&deleted);
+        self.limit += increment;
+        deleted.len()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{phi::Phi, clause::{Clause, Literal}, propagation::Propagator, drat::{Proof, ProofFormat}};
+    use super::{lbd, LearnedClauses, ReductionPolicy};
+
+    #[test]
+    fn reduce()
+    {
+        let mut propagator = Propagator::new(&Phi::new(), 6);
+        for decision in [1, 2, 3]
+        {
+            propagator.decide(Literal::from_isize(decision));
+        }
+        assert_eq!(lbd(&propagator, &Clause::new_cn(&[-1, -2, 4]).literals), 3);
+        assert_eq!(lbd(&propagator, &Clause::new_cn(&[-3, 5, 6]).literals), 2);
+
+        let mut learned = LearnedClauses::new(ReductionPolicy::Lbd{limit: 3, increment: 2});
+        let reason = learned.add(&mut propagator, Clause::new_cn(&[4, -1, -2, -3]).literals, 4);
+        assert_eq!(propagator.value(3), Some(true));
+        let glue = learned.add(&mut propagator, Clause::new_cn(&[-3, 5, 6]).literals, 2);
+        let binary = learned.add(&mut propagator, Clause::new_cn(&[5, 6]).literals, 3);
+        let worse = learned.add(&mut propagator, Clause::new_cn(&[-1, -2, 5, 6]).literals, 4);
+        let better = learned.add(&mut propagator, Clause::new_cn(&[-2, 5, 6]).literals, 3);
+        let others: Vec<usize> = [[-1, 5, 6], [-2, -5, 6]].iter().map(|c| learned.add(&mut propagator, Clause::new_cn(c).literals, 3)).collect();
+        learned.bump(better);
+        assert_eq!((learned.len(), learned.lbd(better), learned.lbd(100)), (7, Some(3), None));
+
+        //the reason of x4, the glue clause and the binary clause are kept,
+        //half of the clauses are deleted among the others, the most active is kept
+        let mut proof = Proof::new(std::io::sink(), ProofFormat::Text);
+        assert_eq!(learned.reduce(&mut propagator, &mut Some(&mut proof)), 3);
+        assert_eq!(proof.deletions(), 3);
+        assert!([worse, others[0], others[1]].iter().all(|c| learned.lbd(*c).is_none()));
+        assert!([reason, glue, binary, better].iter().all(|c| learned.lbd(*c).is_some()));
+        //the limit grew
+        assert_eq!(learned.reduce(&mut propagator, &mut None), 0);
+
+        //the activity policy deletes the clauses that took part in the fewest conflicts
+        let mut learned = LearnedClauses::new(ReductionPolicy::Activity{limit: 1, increment: 1});
+        propagator.backtrack(0);
+        let used = learned.add(&mut propagator, Clause::new_cn(&[1, 2, 3]).literals, 3);
+        let unused = learned.add(&mut propagator, Clause::new_cn(&[1, 2, -3]).literals, 3);
+        learned.decay();
+        learned.bump(used);
+        assert_eq!(learned.reduce(&mut propagator, &mut None), 1);
+        assert_eq!((learned.lbd(used), learned.lbd(unused)), (Some(3), None));
+        assert_eq!(LearnedClauses::new(ReductionPolicy::Never).reduce(&mut propagator, &mut None), 0);
+        assert_eq!("activity".parse::<ReductionPolicy>().unwrap(), ReductionPolicy::Activity{limit: 2000, increment: 300});
+    }
+}
@@ -2,4 +2,16 @@ pub mod clause;
 pub mod phi;
 pub mod solver;
 pub mod error;
-pub mod two_satisfiability;
\ No newline at end of file
+pub mod propagation;
+pub mod two_satisfiability;
+pub mod stats;
+pub mod logging;
+pub mod dimacs;
+pub mod drat;
+pub mod proof;
+pub mod builder;
+pub mod heuristics;
+pub mod restart;
+pub mod learned;
+pub mod implications;
+pub mod ipasir;
@@ -0,0 +1,124 @@
+use log::{LevelFilter, Log, Metadata, Record};
+use crate::error::Error;
+
+/*
+A minimal logger that writes the diagnostics of the solver to stderr.
+The verbosity is a comma separated list of directives: a bare level sets the default one,
+`module=level` sets the level of a module and of its submodules, the longest matching module wins.
+e.g. `warn,colombini_sat::solver=debug,colombini_sat::two_satisfiability=trace`
+Nothing is logged until init is called, so the library is silent by default.
+*/
+
+pub struct Logger
+{
+    default: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Logger
+{
+    ///Parses a list of verbosity directives
+    /// # Arguments
+    /// * `spec` - The directives, an empty string logs nothing
+    /// # Example
+    /// ```
+    /// use colombini_sat::logging::Logger;
+    /// use log::LevelFilter;
+    ///
+    /// let logger = Logger::parse("warn,colombini_sat::solver=trace").unwrap();
+    /// assert_eq!(logger.level("colombini_sat::solver"), LevelFilter::Trace);
+    /// assert_eq!(logger.level("colombini_sat::phi"), LevelFilter::Warn);
+    /// ```
+    pub fn parse(spec: &str) -> Result<Logger, Error>
+    {
+        let mut logger = Logger{default: LevelFilter::Off, modules: Vec::new()};
+        for directive in spec.split(',').map(|d| d.trim()).filter(|d| !d.is_empty())
+        {
+            match directive.split_once('=')
+            {
+                Some((module, level)) => {
+                    let level = level.trim().parse().map_err(|_| Error::new(&format!("Invalid log level in \"{directive}\"")))?;
+                    logger.modules.push((module.trim().to_string(), level));
+                },
+                None => {
+                    logger.default = directive.parse().map_err(|_| Error::new(&format!("Invalid log level \"{directive}\"")))?;
+                }
+            }
+        }
+        Ok(logger)
+    }
+
+    ///returns the verbosity of a module
+    pub fn level(&self, target: &str) -> LevelFilter
+    {
+        let mut best: Option<&(String, LevelFilter)> = None;
+        for entry in &self.modules
+        {
+            let (module, _) = entry;
+            let matches = target == module || (target.starts_with(module.as_str()) && target[module.len()..].starts_with("::"));
+            if matches && best.is_none_or(|(b, _)| b.len() <= module.len()) { best = Some(entry); }
+        }
+        best.map_or(self.default, |(_, level)| *level)
+    }
+
+    ///returns the highest verbosity of any module
+    pub fn max_level(&self) -> LevelFilter
+    {
+        self.modules.iter().map(|(_, level)| *level).fold(self.default, Ord::max)
+    }
+}
+
+impl Log for Logger
+{
+    fn enabled(&self, metadata: &Metadata) -> bool
+    {
+        metadata.level() <= self.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record)
+    {
+        if self.enabled(record.metadata())
+        {
+            eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+///Installs the logger, can only be called once
+/// # Arguments
+/// * `spec` - The verbosity directives, see Logger::parse
+pub fn init(spec: &str) -> Result<(), Box<dyn std::error::Error>>
+{
+    let logger = Logger::parse(spec)?;
+    log::set_max_level(logger.max_level());
+    log::set_logger(Box::leak(Box::new(logger))).map_err(|_| Error::new("A logger was already installed"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use log::LevelFilter;
+    use super::Logger;
+
+    #[test]
+    fn parse()
+    {
+        let logger = Logger::parse("").unwrap();
+        assert_eq!(logger.level("colombini_sat::solver"), LevelFilter::Off);
+        assert_eq!(logger.max_level(), LevelFilter::Off);
+
+        let logger = Logger::parse("info, colombini_sat=warn ,colombini_sat::solver=trace").unwrap();
+        assert_eq!(logger.level("colombini_sat::solver"), LevelFilter::Trace);
+        assert_eq!(logger.level("colombini_sat::solver::inner"), LevelFilter::Trace);
+        assert_eq!(logger.level("colombini_sat::solvers"), LevelFilter::Warn);
+        assert_eq!(logger.level("colombini_sat::phi"), LevelFilter::Warn);
+        assert_eq!(logger.level("petgraph"), LevelFilter::Info);
+        assert_eq!(logger.max_level(), LevelFilter::Trace);
+
+        assert!(Logger::parse("loud").is_err());
+        assert!(Logger::parse("colombini_sat::solver=loud").is_err());
+    }
+}
@@ -1,17 +1,324 @@
-use std::process::exit;
+use std::{io::{self, Write}, process::exit, sync::mpsc, thread, time::Duration};
 
-use colombini_sat::{solver::{Solver, add_decision, add_implication}, clause::{Literal, Clause}, phi::Phi};
-use petgraph::prelude::DiGraph;
+use colombini_sat::{solver::Solver, stats::SolveStats, error::Error, logging, dimacs::ParseMode, drat::{Proof, ProofFormat}, heuristics::Heuristic, restart::{RestartPolicy, PhasePolicy}, learned::ReductionPolicy};
+
+/*
+Command line front end following the conventions of the SAT competitions:
+comments start with "c", the answer is a "s" line and the model is given in "v" lines terminated by 0.
+The exit code is 10 for satisfiable formulas, 20 for unsatisfiable ones and 0 when the answer is unknown.
+*/
+
+const USAGE: &str = "\
+usage: colombini_sat [options] [input.cnf]
+reads the formula from stdin when no input, or -, is given
+
+options:
+  -a, --algorithm <name>    solve (lookahead, the default), dpll or cdcl
+  -t, --time-limit <secs>   answers UNKNOWN when the search takes longer
+  -b, --branching <name>    branching heuristic of the lookahead: lookahead (the default), vsids, jw or moms
+  -s, --seed <n>            seed breaking the ties of the branching heuristic
+  -r, --restarts <name>     restart policy: never (the default), luby, geometric or glucose
+      --phase <name>        value tried first for a branching variable: fixed (the default), saved or target
+      --reduce <name>       deletion of the learned clauses: lbd (the default), activity or never
+  -v, --verbosity <spec>    log directives, e.g. debug or colombini_sat::solver=trace,
+                            defaults to the COLOMBINI_LOG variable
+  -p, --proof <path>        writes a DRAT proof of unsatisfiability
+      --binary-proof        writes the proof in the binary DRAT format
+  -l, --lenient             accepts malformed files and headers that do not match the formula
+      --verify              checks the model on the formula before printing it
+      --stats               prints the statistics of the search as comments
+  -h, --help                prints this message";
+
+const SATISFIABLE: i32 = 10;
+const UNSATISFIABLE: i32 = 20;
+const UNKNOWN: i32 = 0;
+const FAILURE: i32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Algorithm
+{
+    Solve,
+    Dpll,
+    Cdcl
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Options
+{
+    input: Option<String>,
+    algorithm: Algorithm,
+    time_limit: Option<Duration>,
+    heuristic: Heuristic,
+    seed: u64,
+    restarts: RestartPolicy,
+    phase: PhasePolicy,
+    reduction: ReductionPolicy,
+    mode: ParseMode,
+    proof: Option<String>,
+    proof_format: ProofFormat,
+    verbosity: Option<String>,
+    verify: bool,
+    stats: bool,
+    help: bool
+}
+
+impl Default for Options
+{
+    fn default() -> Self
+    {
+        Options{input: None, algorithm: Algorithm::Solve, time_limit: None, heuristic: Heuristic::Lookahead, seed: 0, restarts: RestartPolicy::Never, phase: PhasePolicy::Fixed, reduction: ReductionPolicy::default(), mode: ParseMode::Strict, proof: None, proof_format: ProofFormat::Text, verbosity: None, verify: false, stats: false, help: false}
+    }
+}
+
+///Parses the command line arguments, the name of the program excluded
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, Error>
+{
+    let mut options = Options::default();
+    while let Some(arg) = args.next()
+    {
+        let mut value = |name: &str| args.next().ok_or_else(|| Error::new(&format!("missing value for {name}")));
+        match arg.as_str()
+        {
+            "-a" | "--algorithm" => {
+                options.algorithm = match value(&arg)?.as_str()
+                {
+                    "solve" => Algorithm::Solve,
+                    "dpll" => Algorithm::Dpll,
+                    "cdcl" => Algorithm::Cdcl,
+                    other => return Err(Error::new(&format!("unknown algorithm \"{other}\"")))
+                };
+            },
+            "-t" | "--time-limit" => {
+                let seconds: f64 = value(&arg)?.parse().map_err(|_| Error::new("the time limit must be a number of seconds"))?;
+                options.time_limit = Some(Duration::try_from_secs_f64(seconds).map_err(|_| Error::new("the time limit must be a number of seconds"))?);
+            },
+            "-b" | "--branching" => options.heuristic = value(&arg)?.parse()?,
+            "-s" | "--seed" => {
+                options.seed = value(&arg)?.parse().map_err(|_| Error::new("the seed must be a non negative integer"))?;
+            },
+            "-r" | "--restarts" => options.restarts = value(&arg)?.parse()?,
+            "--phase" => options.phase = value(&arg)?.parse()?,
+            "--reduce" => options.reduction = value(&arg)?.parse()?,
+            "-v" | "--verbosity" => options.verbosity = Some(value(&arg)?),
+            "-p" | "--proof" => options.proof = Some(value(&arg)?),
+            "--binary-proof" => options.proof_format = ProofFormat::Binary,
+            "-l" | "--lenient" => options.mode = ParseMode::Lenient,
+            "--verify" => options.verify = true,
+            "--stats" => options.stats = true,
+            "-h" | "--help" => options.help = true,
+            "-" => options.input = None,
+            _ if arg.starts_with('-') => return Err(Error::new(&format!("unknown option \"{arg}\""))),
+            _ if options.input.is_some() => return Err(Error::new("only one input file can be given")),
+            _ => options.input = Some(arg)
+        }
+    }
+    Ok(options)
+}
+
+///Writes the model in "v" lines of at most 80 characters, the last one is terminated by 0
+fn write_model<W: Write>(out: &mut W, model: &[isize]) -> io::Result<()>
+{
+    let mut line = String::from("v");
+    for lit in model.iter().map(|l| l.to_string()).chain(std::iter::once("0".to_string()))
+    {
+        if line.len() + lit.len() + 1 > 80
+        {
+            writeln!(out, "{line}")?;
+            line = String::from("v");
+        }
+        line.push(' ');
+        line.push_str(&lit);
+    }
+    writeln!(out, "{line}")
+}
+
+///Reads the formula and searches it with the chosen algorithm
+fn run(options: &Options) -> Result<(Option<Vec<isize>>, SolveStats), Error>
+{
+    let (mut solver, warnings) = match &options.input
+    {
+        Some(path) => Solver::create_with_mode(path, options.mode)?,
+        None => Solver::from_reader_with_mode(io::stdin().lock(), options.mode)?
+    };
+    for warning in warnings
+    {
+        println!("c warning: {warning}");
+    }
+    println!("c {} variables, {} clauses", solver.num_variables(), solver.num_clauses());
+    solver.set_verify(options.verify);
+    solver.set_heuristic(options.heuristic);
+    solver.set_seed(options.seed);
+    solver.set_restarts(options.restarts)?;
+    solver.set_phase(options.phase);
+    solver.set_reduction(options.reduction);
+    let mut proof = match &options.proof
+    {
+        Some(path) => Some(Proof::create(path, options.proof_format)?),
+        None => None
+    };
+    let result = match (options.algorithm, proof.as_mut())
+    {
+        (Algorithm::Solve, None) => solver.solve_with_stats()?,
+        (Algorithm::Dpll, None) => solver.solve_dpll_with_stats()?,
+        (Algorithm::Cdcl, None) => solver.solve_cdcl_with_stats()?,
+        (Algorithm::Solve, Some(proof)) => solver.solve_with_proof(proof)?,
+        (Algorithm::Dpll, Some(proof)) => solver.solve_dpll_with_proof(proof)?,
+        (Algorithm::Cdcl, Some(proof)) => solver.solve_cdcl_with_proof(proof)?
+    };
+    if let Some(proof) = proof
+    {
+        println!("c {} clauses written to the proof", proof.additions());
+        proof.finish()?;
+    }
+    Ok(result)
+}
 
 fn main() {
-    let solver = Solver::create("TestData/solver20-0.cnf").unwrap();
-    
-    println!("Solving 3-SAT formula with {} variables and {} clauses", solver.num_variables(), solver.num_clauses());
-    let start = std::time::Instant::now();
-    let solution = solver.solve();
-    match solution{
-        Some(solution) => println!("SAT: ({:?})", solution),
-        None => println!("UNSAT")
-    }
-    println!("Elapsed: {:?}", start.elapsed());
+    let options = match parse_args(std::env::args().skip(1))
+    {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            exit(FAILURE);
+        }
+    };
+    if options.help
+    {
+        println!("{USAGE}");
+        exit(UNKNOWN);
+    }
+    //diagnostics are silent unless asked for, e.g. -v debug or COLOMBINI_LOG=colombini_sat::solver=trace
+    let verbosity = options.verbosity.clone().unwrap_or_else(|| std::env::var("COLOMBINI_LOG").unwrap_or_default());
+    if let Err(e) = logging::init(&verbosity)
+    {
+        eprintln!("{e}");
+        exit(FAILURE);
+    }
+    //the seed is reported so that any run can be reproduced
+    println!("c seed {}", options.seed);
+
+    //the search runs on its own thread so that it can be abandoned when the time limit expires
+    let (sender, receiver) = mpsc::channel();
+    let search = options.clone();
+    let spawned = thread::Builder::new().spawn(move || {
+        //the receiver is gone only if the time limit expired
+        let _ = sender.send(run(&search));
+    });
+    if let Err(e) = spawned
+    {
+        eprintln!("{e}");
+        exit(FAILURE);
+    }
+    let result = match options.time_limit
+    {
+        Some(limit) => receiver.recv_timeout(limit).map_err(|e| e == mpsc::RecvTimeoutError::Disconnected),
+        None => receiver.recv().map_err(|_| true)
+    };
+    //the sender is dropped without sending only if the search panicked
+    if matches!(result, Err(true))
+    {
+        eprintln!("c error: the search stopped unexpectedly");
+        exit(FAILURE);
+    }
+
+    let code = match result
+    {
+        Ok(Ok((solution, stats))) => {
+            if options.stats
+            {
+                for line in stats.to_string().lines()
+                {
+                    println!("c {line}");
+                }
+            }
+            match solution
+            {
+                Some(model) => {
+                    println!("s SATISFIABLE");
+                    if let Err(e) = write_model(&mut io::stdout().lock(), &model)
+                    {
+                        eprintln!("{e}");
+                        exit(FAILURE);
+                    }
+                    SATISFIABLE
+                },
+                None => {
+                    println!("s UNSATISFIABLE");
+                    UNSATISFIABLE
+                }
+            }
+        },
+        Ok(Err(e)) => {
+            eprintln!("c error: {e}");
+            exit(FAILURE);
+        },
+        Err(_) => {
+            println!("c time limit expired");
+            println!("s UNKNOWN");
+            UNKNOWN
+        }
+    };
+    let _ = io::stdout().flush();
+    exit(code);
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+    use colombini_sat::{dimacs::ParseMode, drat::ProofFormat, heuristics::Heuristic, restart::{RestartPolicy, PhasePolicy}, learned::ReductionPolicy};
+    use super::{parse_args, write_model, Algorithm, Options};
+
+    fn args(line: &str) -> impl Iterator<Item = String> + '_
+    {
+        line.split_whitespace().map(|a| a.to_string())
+    }
+
+    #[test]
+    fn arguments()
+    {
+        assert_eq!(parse_args(args("")).unwrap(), Options::default());
+        let options = parse_args(args("-a dpll --time-limit 1.5 -b moms -s 7 -r glucose --phase target --reduce never -v debug -l -p out.drat --binary-proof --verify --stats in.cnf")).unwrap();
+        assert_eq!(options.algorithm, Algorithm::Dpll);
+        assert_eq!(options.time_limit, Some(Duration::from_millis(1500)));
+        assert_eq!(options.heuristic, Heuristic::Moms);
+        assert_eq!(options.seed, 7);
+        assert_eq!((options.restarts, options.phase), (RestartPolicy::Glucose, PhasePolicy::Target));
+        assert_eq!(options.reduction, ReductionPolicy::Never);
+        assert_eq!(options.verbosity.as_deref(), Some("debug"));
+        assert_eq!(options.mode, ParseMode::Lenient);
+        assert_eq!(options.proof.as_deref(), Some("out.drat"));
+        assert_eq!(options.proof_format, ProofFormat::Binary);
+        assert!(options.verify);
+        assert!(options.stats);
+        assert_eq!(options.input.as_deref(), Some("in.cnf"));
+        assert_eq!(parse_args(args("-")).unwrap().input, None);
+
+        assert!(parse_args(args("-a walksat")).is_err());
+        assert!(parse_args(args("-b random")).is_err());
+        assert!(parse_args(args("-r sometimes")).is_err());
+        assert!(parse_args(args("--phase random")).is_err());
+        assert!(parse_args(args("--reduce all")).is_err());
+        assert!(parse_args(args("-t")).is_err());
+        assert!(parse_args(args("-t -1")).is_err());
+        assert!(parse_args(args("--fast")).is_err());
+        assert!(parse_args(args("a.cnf b.cnf")).is_err());
+    }
+
+    #[test]
+    fn model_lines()
+    {
+        let mut out = Vec::new();
+        write_model(&mut out, &[1, -2, 3]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "v 1 -2 3 0\n");
+
+        let model: Vec<isize> = (1..=100).map(|i| -i).collect();
+        let mut out = Vec::new();
+        write_model(&mut out, &model).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.lines().all(|l| l.starts_with("v ") && l.len() <= 80));
+        assert!(text.ends_with(" -100 0\n"));
+        let literals: Vec<isize> = text.split_whitespace().filter(|w| *w != "v").map(|w| w.parse().unwrap()).collect();
+        assert_eq!(literals[..100], model[..]);
+    }
 }
@@ -1,11 +1,34 @@
 
-use std::{fs, collections::HashSet};
-use crate::{clause::{Clause, Literal}, error::Error};
+use std::{fs::File, io::{self, BufRead, BufReader, BufWriter, Write}, str::FromStr};
+use log::debug;
+use crate::{clause::{Clause, Literal}, error::{Error, Position}, propagation::Propagator, dimacs::{Parser, ParseMode, Header}};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Phi
 {
-    pub clauses: Vec<Clause>,
+    clauses: Vec<Clause>,
+    //for every literal, the indices of the clauses it appears in, in ascending order
+    occurrences: Vec<Vec<usize>>,
+    //variables declared by the dimacs header, the last ones may not appear in any clause
+    declared_vars: usize,
+    //the comments of the dimacs file, without the leading c
+    comments: Vec<String>,
+}
+
+fn literal_code(index: usize, value: bool) -> usize
+{
+    2*index + value as usize
+}
+
+///Parses a dimacs formula
+impl FromStr for Phi
+{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Phi,Error>
+    {
+        Phi::from_reader(s.as_bytes())
+    }
 }
 
 impl std::fmt::Display for Phi
@@ -22,41 +45,134 @@ impl std::fmt::Display for Phi
     }
 }
 
+impl Default for Phi
+{
+    fn default() -> Self
+    {
+        Phi::new()
+    }
+}
+
 impl Phi{
     pub fn new() -> Phi
     {
         Phi{
             clauses: Vec::new(),
+            occurrences: Vec::new(),
+            declared_vars: 0,
+            comments: Vec::new(),
         }
     }
 
     pub fn new_unsat() -> Phi
     {
-        Phi{
-            clauses: vec![Clause::Empty],
+        Phi::from_clauses(vec![Clause::empty()])
+    }
+
+    ///Creates a phi with the given clauses, indexing their literals
+    /// # Example
+    /// ```
+    /// use colombini_sat::{phi::Phi, clause::Clause};
+    /// let phi = Phi::from_clauses(vec![Clause::new_c3(1,-2,3), Clause::new_c2(2,-3)]);
+    /// assert_eq!(phi.occurrences(1, false), &[0]);
+    /// ```
+    pub fn from_clauses(clauses: Vec<Clause>) -> Phi
+    {
+        let mut ret = Phi::new();
+        ret.clauses.reserve(clauses.len());
+        for c in clauses
+        {
+            ret.add_clause(c);
         }
+        ret
     }
 
-    pub fn vars(&self) -> usize
+    pub fn clauses(&self) -> &[Clause]
     {
-        let mut ret: usize = 0;
-        for c in &self.clauses
+        &self.clauses
+    }
+
+    ///Appends a clause keeping the occurrence lists up to date
+    /// # Returns
+    /// * The index of the clause
+    pub fn add_clause(&mut self, clause: Clause) -> usize
+    {
+        let index = self.clauses.len();
+        for lit in &clause.literals
         {
-            let max = c.max_literal();
-            if max > ret { ret = max; }
+            let code = literal_code(lit.index, lit.value);
+            if self.occurrences.len() <= code
+            {
+                self.occurrences.resize(2*(lit.index+1), Vec::new());
+            }
+            //a literal repeated in the clause is only indexed once
+            if self.occurrences[code].last() != Some(&index) { self.occurrences[code].push(index); }
         }
-        ret + 1
+        self.clauses.push(clause);
+        index
+    }
+
+    ///returns the indices of the clauses containing the literal, in ascending order
+    /// # Arguments
+    /// * `index` - The 0-based index of the variable
+    /// * `value` - The polarity of the literal
+    pub fn occurrences(&self, index: usize, value: bool) -> &[usize]
+    {
+        self.occurrences.get(literal_code(index, value)).map_or(&[], |o| o.as_slice())
+    }
+
+    ///returns the indices of the clauses containing the variable with either polarity, in ascending order
+    pub fn var_occurrences(&self, index: usize) -> Vec<usize>
+    {
+        let positive = self.occurrences(index, true);
+        let negative = self.occurrences(index, false);
+        let mut ret = Vec::with_capacity(positive.len() + negative.len());
+        let (mut i, mut j) = (0, 0);
+        while i < positive.len() || j < negative.len()
+        {
+            if j == negative.len() || (i < positive.len() && positive[i] < negative[j]) { ret.push(positive[i]); i += 1; }
+            else if i == positive.len() || negative[j] < positive[i] { ret.push(negative[j]); j += 1; }
+            //a tautological clause contains both literals
+            else { ret.push(positive[i]); i += 1; j += 1; }
+        }
+        ret
+    }
+
+    ///returns the comments read with the formula
+    pub fn comments(&self) -> &[String]
+    {
+        &self.comments
+    }
+
+    ///Adds a comment line, written before the header by write_dimacs
+    pub fn add_comment(&mut self, comment: &str)
+    {
+        self.comments.extend(comment.lines().map(|l| l.to_string()));
+    }
+
+    ///returns the number of variables, the ones declared by the header are counted even if they are unused
+    pub fn vars(&self) -> usize
+    {
+        (self.occurrences.len() / 2).max(self.declared_vars)
+    }
+
+    ///Counts at least the given number of variables, as the header of a dimacs file does
+    pub fn declare_vars(&mut self, vars: usize)
+    {
+        self.declared_vars = self.declared_vars.max(vars);
     }
 
     pub fn reduce(&self, values: &[Option<bool>]) -> Phi
     {
         let mut ret = Phi::new();
+        ret.declared_vars = self.declared_vars;
+        ret.comments = self.comments.clone();
         for c in &self.clauses
         {
             if let Some(new_clause) = c.reduce(values)
             {
-                if let Clause::Empty = new_clause { return Phi::new_unsat(); }
-                ret.clauses.push(new_clause);
+                if new_clause.is_empty() { return Phi::new_unsat(); }
+                ret.add_clause(new_clause);
             }
         }
         ret
@@ -65,12 +181,14 @@ impl Phi{
     pub fn adapt(&self) -> Phi
     {
         let mut ret = Phi::new();
+        ret.declared_vars = self.declared_vars;
+        ret.comments = self.comments.clone();
         for c in &self.clauses
         {
             if let Some(new_clause) = c.adapt()
             {
-                if let Clause::Empty = new_clause { return Phi::new_unsat(); }
-                ret.clauses.push(new_clause);
+                if new_clause.is_empty() { return Phi::new_unsat(); }
+                ret.add_clause(new_clause);
             }
         }
         ret
@@ -81,31 +199,33 @@ impl Phi{
         let mut ret: Vec<Clause> = Vec::new();
         for c in &self.clauses
         {
-            if let Clause::C1(lit) = c
+            if let [lit] = c.literals.as_slice()
             {
-                if !lit.assigned{ret.push(*c);}
+                if !lit.assigned{ret.push(c.clone());}
             }
         }
         ret
     }
 
+    ///Propagates the unit clauses of phi together with the given assignments
+    /// # Arguments
+    /// * `assignments` - The values already assigned, extended with the propagated ones
+    /// # Returns
+    /// * phi reduced by the assignments, or an unsatisfiable phi if propagation found a conflict
     pub fn autoreduce_with_assignments(&self, assignments: &mut Vec<Option<bool>>) -> Phi
     {
-        let units = self.adapt().find_unit();
-        if units.len() == 0 {self.reduce(&assignments)}
-        else 
+        let mut propagator = Propagator::new(self, assignments.len());
+        for (index, value) in assignments.iter().enumerate()
         {
-            for c in &units
+            if let Some(value) = value
             {
-                if let Clause::C1(l) = c
-                {
-                    if assignments[l.index] != None { continue; }
-                    assignments[l.index] = Some(l.value);
-                }
+                if !propagator.assign(Literal{index, value: *value, implicated: false, assigned: false}, None) { return Phi::new_unsat(); }
             }
-            let phi = self.reduce(&assignments);
-            return phi.autoreduce_with_assignments(assignments);    
         }
+        if propagator.propagate().is_some() { return Phi::new_unsat(); }
+        assignments.clear();
+        assignments.extend_from_slice(propagator.assignments());
+        self.reduce(assignments)
     }
 
     pub fn autoreduce(&self) -> Phi
@@ -117,7 +237,7 @@ impl Phi{
             let mut values: Vec<Option<bool>> = vec![None; self.vars()];
             for c in &units
             {
-                if let Clause::C1(l) = c
+                if let [l] = c.literals.as_slice()
                 {
                     values[l.index] = Some(l.value);
                 }
@@ -127,59 +247,166 @@ impl Phi{
         }
     }
 
-    pub fn from_file(path: &str) -> Result<Phi,Box<dyn std::error::Error>>
+    pub fn from_file(path: &str) -> Result<Phi,Error>
+    {
+        let (phi, _) = Phi::from_file_with_mode(path, ParseMode::Strict)?;
+        Ok(phi)
+    }
+
+    ///Reads a dimacs file
+    /// # Arguments
+    /// * `path` - The path to the dimacs file
+    /// * `mode` - In strict mode any deviation from the format is an error, in lenient mode it is a warning
+    /// # Returns
+    /// * The formula and the warnings collected in lenient mode
+    pub fn from_file_with_mode(path: &str, mode: ParseMode) -> Result<(Phi,Vec<Error>),Error>
+    {
+        let (phi, _, warnings) = Phi::parse(BufReader::new(File::open(path)?), false, mode)?;
+        debug!("parsed {path}: {} clauses over {} variables, {} warnings", phi.clauses.len(), phi.vars(), warnings.len());
+        Ok((phi, warnings))
+    }
+
+    ///Reads a dimacs formula from any reader, e.g. stdin or a byte slice
+    /// # Example
+    /// ```
+    /// use colombini_sat::phi::Phi;
+    ///
+    /// let phi = Phi::from_reader("p cnf 3 2\n1 -2 0 2\n3 0\n".as_bytes()).unwrap();
+    /// assert_eq!(phi.clauses().len(), 2);
+    /// ```
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Phi,Error>
+    {
+        let (phi, _) = Phi::from_reader_with_mode(reader, ParseMode::Strict)?;
+        Ok(phi)
+    }
+
+    ///Same as from_file_with_mode, reading the formula from any reader
+    /// # Example
+    /// ```
+    /// use colombini_sat::{phi::Phi, dimacs::ParseMode};
+    ///
+    /// //the header declares one clause too many and a variable that is not used
+    /// let (phi, warnings) = Phi::from_reader_with_mode("p cnf 4 3\n1 -2 0\n3 0\n".as_bytes(), ParseMode::Lenient).unwrap();
+    /// assert_eq!(phi.vars(), 4);
+    /// assert_eq!(warnings.len(), 1);
+    /// ```
+    pub fn from_reader_with_mode<R: BufRead>(reader: R, mode: ParseMode) -> Result<(Phi,Vec<Error>),Error>
+    {
+        let (phi, _, warnings) = Phi::parse(reader, false, mode)?;
+        Ok((phi, warnings))
+    }
+
+    ///Reads a dimacs file splitting every clause wider than three literals in a chain of 3-SAT clauses
+    /// # Arguments
+    /// * `path` - The path to the dimacs file
+    /// # Returns
+    /// * The 3-SAT formula and the number of variables declared in the header,
+    ///   every variable after them was introduced by the conversion
+    pub fn from_file_3sat(path: &str) -> Result<(Phi,usize),Error>
+    {
+        Phi::from_reader_3sat(BufReader::new(File::open(path)?))
+    }
+
+    ///Same as from_file_3sat, reading the formula from any reader
+    pub fn from_reader_3sat<R: BufRead>(reader: R) -> Result<(Phi,usize),Error>
+    {
+        let (phi, num_vars, _) = Phi::parse(reader, true, ParseMode::Strict)?;
+        Ok((phi, num_vars))
+    }
+
+    fn parse<R: BufRead>(reader: R, to_3sat: bool, mode: ParseMode) -> Result<(Phi,usize,Vec<Error>),Error>
     {
         let mut ret = Phi::new();
-        //open file and read it
-        let contents = fs::read_to_string(path)?;
-        let clear_contents = contents.replace('\r',"");
-        //split the file in lines
-        let lines: Vec<&str> = clear_contents.split("\n").collect();
-        let mut num_clauses: usize = 0;
-        let mut num_vars: usize = 0;
+        let mut parser = Parser::with_mode(reader, mode);
         let mut inserted_clauses: usize = 0;
-        //for each line
-        for line in lines
+        //the highest variable of the clauses, None while no literal is read
+        let mut max_index: Option<usize> = None;
+        //variables added by the 3-SAT conversion, numbered after the ones in the header
+        let mut new_vars: usize = 0;
+        while let Some(clause) = parser.next()
         {
-            //check if line is empty
-            if line.len() == 0 { continue; }
-            //check if line is a comment
-            else if line.chars().nth(0).unwrap() == 'c' { continue; }
-            //check if line is a problem line
-            else if line.chars().nth(0).unwrap() == 'p' 
+            let (c, _) = clause?;
+            max_index = max_index.max(c.literals.iter().map(|l| l.index).max());
+            if to_3sat && c.len() > 3
             {
-                //split the line in words
-                let words: Vec<&str> = line.split(" ").collect();
-                //remove empty words
-                let words: Vec<&str> = words.iter().filter(|x| x.len() > 0).map(|x| *x).collect();
-                //problem is cnf
-                assert_eq!(words[1], "cnf");
-                //get the number of variables
-                num_vars = words[2].parse()?;
-                //get the number of clauses
-                num_clauses = words[3].parse()?;
-                //initialize the vector of clauses
-                ret.clauses = Vec::with_capacity(num_clauses);
+                //the header is always read before the first clause
+                let num_vars = parser.header().map_or(0, |h| h.vars);
+                let chain = Clause::from_k_clause(c.literals, num_vars + new_vars);
+                //a chain of k-2 clauses links them with k-3 new variables
+                new_vars += chain.len() - 1;
+                for c in chain { ret.add_clause(c); }
             }
-            else 
+            else { ret.add_clause(c); }
+            inserted_clauses += 1;
+        }
+        let used_vars = max_index.map_or(0, |index| index + 1);
+        //in lenient mode the formula may have no header
+        let header = parser.header().unwrap_or(Header{vars: used_vars, clauses: inserted_clauses, at: Position::new(1, 1)});
+        ret.comments = parser.comments().to_vec();
+        let mut warnings = parser.into_warnings();
+        if header.clauses != inserted_clauses || header.vars != used_vars
+        {
+            let mismatch = Error::HeaderMismatch{
+                at: header.at,
+                declared_vars: header.vars,
+                declared_clauses: header.clauses,
+                vars: used_vars,
+                clauses: inserted_clauses
+            };
+            match mode
             {
-                let clause = Clause::from_str(line);
-                match clause
-                {
-                    Ok(c) => {ret.clauses.push(c);},
-                    Err(_) => {continue;}
-                }
-                inserted_clauses += 1;
+                ParseMode::Strict => return Err(mismatch),
+                ParseMode::Lenient => warnings.push(mismatch)
             }
         }
-        if num_clauses != inserted_clauses || num_vars != ret.vars() 
+        ret.declared_vars = header.vars;
+        Ok((ret, header.vars, warnings))
+    }
+
+    ///Writes the formula in dimacs format, the header declares vars() variables
+    /// # Arguments
+    /// * `writer` - Where the formula is written, e.g. a file or stdout
+    /// * `keep_comments` - Writes the comments of the formula before the header
+    /// # Example
+    /// ```
+    /// use colombini_sat::phi::Phi;
+    ///
+    /// let phi: Phi = "c generated\np cnf 3 2\n1 -2 3 0 -3 0\n".parse().unwrap();
+    /// let mut out = Vec::new();
+    /// phi.write_dimacs(&mut out, true).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), "c generated\np cnf 3 2\n1 -2 3 0\n-3 0\n");
+    /// ```
+    pub fn write_dimacs<W: Write>(&self, mut writer: W, keep_comments: bool) -> io::Result<()>
+    {
+        if keep_comments
         {
-            Err(Error::new("Error while parsing the file").into())
+            for comment in &self.comments
+            {
+                if comment.is_empty() { writeln!(writer, "c")?; }
+                else { writeln!(writer, "c {comment}")?; }
+            }
         }
-        else
-        {  
-            Ok(ret) 
+        writeln!(writer, "p cnf {} {}", self.vars(), self.clauses.len())?;
+        for c in &self.clauses
+        {
+            for lit in &c.literals
+            {
+                write!(writer, "{} ", lit.as_isize())?;
+            }
+            writeln!(writer, "0")?;
         }
+        writer.flush()
+    }
+
+    ///Writes the formula to a dimacs file, replacing it if it exists
+    /// # Arguments
+    /// * `path` - The path of the file
+    /// * `keep_comments` - Writes the comments of the formula before the header
+    pub fn to_file(&self, path: &str, keep_comments: bool) -> Result<(),Error>
+    {
+        self.write_dimacs(BufWriter::new(File::create(path)?), keep_comments)?;
+        debug!("wrote {path}: {} clauses over {} variables", self.clauses.len(), self.vars());
+        Ok(())
     }
 
     pub fn eval(&self, assignment: &Vec<bool>) -> bool
@@ -194,46 +421,42 @@ impl Phi{
         true
     }
 
+    ///returns the index of the first clause falsified by the assignment, None if the assignment satisfies phi
+    /// # Example
+    /// ```
+    /// use colombini_sat::{phi::Phi, clause::Clause};
+    ///
+    /// let phi = Phi::from_clauses(vec![Clause::new_c2(1,2), Clause::new_c2(-1,2)]);
+    /// assert_eq!(phi.falsified(&[true, true]), None);
+    /// assert_eq!(phi.falsified(&[true, false]), Some(1));
+    /// ```
+    pub fn falsified(&self, assignment: &[bool]) -> Option<usize>
+    {
+        self.clauses.iter().position(|c| !c.eval(assignment))
+    }
+
     pub fn phi_prime(&self, index: usize) -> Phi
     {
         //get the clauses where literal with index index is present
-        let mut clauses: Vec<Clause> = Vec::new();
-        for clause in &self.clauses{ if clause.contains(index) && !clause.is_implicated(){ clauses.push(*clause); } }
-        Phi{clauses}
+        let mut ret = Phi::new();
+        for c in self.var_occurrences(index)
+        {
+            if !self.clauses[c].is_implicated() { ret.add_clause(self.clauses[c].clone()); }
+        }
+        ret
     }
 
+    ///returns a unit clause for every pure variable, that appears with a single polarity among the non unit clauses
     pub fn create_unit(&self) -> Vec<Clause>
     {
-        let var_count = self.vars();
-        let mut fixed_vars: Vec<Result<bool,&str>> = vec![Err("not_init"); var_count];
-        for c in &self.clauses
-        {
-            let literals = c.literals_vector();
-            if literals.len() > 1
-            {
-                for l in &literals
-                {
-                    match fixed_vars[l.index]
-                    {
-                        Err(e) => {
-                            match e {
-                                "not_init" => {fixed_vars[l.index] = Ok(l.value);},
-                                _ => {}
-                            }
-                        },
-                        Ok(lit) => {
-                            if lit != l.value {fixed_vars[l.index] = Err("not_fixed");}
-                        }
-                    }
-                }
-            }
-        }
         let mut new_units = Vec::new();
-        for (index,v) in fixed_vars.iter().enumerate()
+        for index in 0..self.occurrences.len() / 2
         {
-            match v {
-                Ok(b) => {new_units.push(Clause::C1(Literal{index: index, value: *b, implicated: false,assigned:false}))},
-                Err(_) => {}
+            let positive = self.occurrences(index, true).iter().any(|c| self.clauses[*c].len() > 1);
+            let negative = self.occurrences(index, false).iter().any(|c| self.clauses[*c].len() > 1);
+            if positive != negative
+            {
+                new_units.push(Clause::new(vec![Literal{index, value: positive, implicated: false, assigned: false}]));
             }
         }
         new_units
@@ -241,82 +464,81 @@ impl Phi{
     /// Create a new Phi with all the unit clauses derived from fixed variables
     pub fn add_unit(&self) -> Phi
     {
-        let mut new_units = self.create_unit();
-        let mut ret = Phi { clauses:  self.clauses.clone()};
-        ret.clauses.append(&mut new_units);
+        let mut ret = self.clone();
+        for unit in self.create_unit()
+        {
+            ret.add_clause(unit);
+        }
         ret
     }
 
+    ///returns the variables with at least one unassigned occurrence, in ascending order
     pub fn get_variables(&self) -> Vec<usize>{
-        let mut variables: HashSet<usize> = HashSet::new();
-        for clause in &self.clauses{
-            for literal in clause.get_variables(){
-                if !variables.contains(&literal.index)&&!literal.assigned{
-                    variables.insert(literal.index);
-                }
+        let mut variables: Vec<usize> = Vec::new();
+        for index in 0..self.occurrences.len() / 2{
+            let unassigned = |c: &usize| self.clauses[*c].literals.iter().any(|l| l.index == index && !l.assigned);
+            if self.occurrences(index, true).iter().any(unassigned) || self.occurrences(index, false).iter().any(unassigned){
+                variables.push(index);
             }
         }
-        let variables: Vec<usize> = variables.into_iter().collect();
         variables
     }
 
     pub fn invert_literal(&mut self,index: usize){
-        for clause in &mut self.clauses{
-            clause.invert_literal(index);
+        if 2*index >= self.occurrences.len(){ return; }
+        let (mut positive, mut negative) = (Vec::new(), Vec::new());
+        for c in self.var_occurrences(index){
+            self.clauses[c].invert_literal(index);
+            let literals = &self.clauses[c].literals;
+            if literals.iter().any(|l| l.index == index && l.value){ positive.push(c); }
+            if literals.iter().any(|l| l.index == index && !l.value){ negative.push(c); }
         }
+        self.occurrences[literal_code(index, true)] = positive;
+        self.occurrences[literal_code(index, false)] = negative;
     }
 
     pub fn get_added_clauses(&self) -> Vec<Clause> {
         let mut implications: Vec<Clause> = Vec::new();
         for clause in &self.clauses{
             if clause.is_implicated(){
-                implications.push(*clause);
+                implications.push(clause.clone());
             }
         }
         implications
     }
 
+    ///returns the clauses where every literal but one is assigned, so the remaining one is implied
     pub fn get_implications(&self) -> Vec<Clause> {
         let mut implications: Vec<Clause> = Vec::new();
         for clause in &self.clauses{
-            match clause{
-                Clause::C3(l1,l2,l3) => {
-                    let mut num_chosen: usize = 0;
-                    if l1.assigned {num_chosen += 1}
-                    if l2.assigned {num_chosen += 1}
-                    if l3.assigned {num_chosen += 1}
-                    if num_chosen == 2 {
-                        implications.push(*clause);
-                    }
-                }
-                Clause::C2(l1,l2) => {
-                    let mut num_chosen: usize = 0;
-                    if l1.assigned {num_chosen += 1}
-                    if l2.assigned {num_chosen += 1}
-                    if num_chosen == 1 {
-                        implications.push(*clause);
-                    }
-                }
-                Clause::C1(_) => {implications.push(*clause)}
-                _ => {unreachable!()}
-                }
+            let num_chosen = clause.literals.iter().filter(|l| l.assigned).count();
+            if clause.len() == 1 || (clause.len() > 1 && num_chosen == clause.len() - 1){
+                implications.push(clause.clone());
             }
+        }
         implications
     }
 
     pub fn update_implications(&mut self, clause: &Clause){
-        match clause{
-            Clause::C1(lit)=>{
-                for clause in &mut self.clauses{
-                    if clause.contains(lit.index){
-                        clause.update_implications(&lit);
-                    }
-                }
+        if let [lit] = clause.literals.as_slice(){
+            for c in self.var_occurrences(lit.index){
+                self.clauses[c].update_implications(lit);
             }
-            _=>{}
         }
     }
 
+    ///returns the number of literals of the widest clause
+    pub fn max_clause_width(&self) -> usize
+    {
+        self.clauses.iter().map(|c| c.len()).max().unwrap_or(0)
+    }
+
+    ///returns true if no clause has more than three literals
+    pub fn is_3_sat(&self) -> bool
+    {
+        self.max_clause_width() <= 3
+    }
+
 }
 
 #[cfg(test)]
@@ -326,6 +548,83 @@ mod tests
 
     use super::*;
 
+    #[test]
+    fn parse_errors()
+    {
+        match Phi::from_file("TestData/bad_literal.cnf")
+        {
+            Err(Error::BadLiteral{at, token}) => {
+                assert_eq!(at, Position::new(4, 3));
+                assert_eq!(token, "x");
+            },
+            other => panic!("expected a bad literal, got {:?}", other)
+        }
+        match Phi::from_file("TestData/mismatch.cnf")
+        {
+            Err(Error::HeaderMismatch{at, declared_vars, declared_clauses, vars, clauses}) => {
+                assert_eq!(at, Position::new(1, 1));
+                assert_eq!((declared_vars, declared_clauses, vars, clauses), (4, 2, 3, 2));
+            },
+            other => panic!("expected a header mismatch, got {:?}", other)
+        }
+        assert!(matches!(Phi::from_file("TestData/no_header.cnf"), Err(Error::HeaderMissing{at: Position{line: 1, column: 1}})));
+
+        //lenient mode turns the errors in warnings
+        let (phi, warnings) = Phi::from_file_with_mode("TestData/mismatch.cnf", ParseMode::Lenient).unwrap();
+        assert_eq!(phi.vars(), 4);
+        assert!(matches!(warnings.as_slice(), [Error::HeaderMismatch{declared_vars: 4, vars: 3, ..}]));
+        let (phi, warnings) = Phi::from_file_with_mode("TestData/bad_literal.cnf", ParseMode::Lenient).unwrap();
+        assert_eq!(phi.clauses.len(), 2);
+        assert!(matches!(warnings.as_slice(), [Error::BadLiteral{..}]));
+        let (phi, warnings) = Phi::from_file_with_mode("TestData/uf50-01.cnf", ParseMode::Lenient).unwrap();
+        assert_eq!(phi, Phi::from_file("TestData/uf50-01.cnf").unwrap());
+        assert!(warnings.is_empty());
+        assert!(matches!(Phi::from_file("TestData/missing.cnf"), Err(Error::Io{at: None, ..})));
+
+        //a formula without literals has no variables
+        assert_eq!(Phi::from_reader("p cnf 0 0\n".as_bytes()).unwrap().vars(), 0);
+        assert!(matches!(Phi::from_reader("p cnf 1 0\n".as_bytes()), Err(Error::HeaderMismatch{declared_vars: 1, vars: 0, ..})));
+        //an empty clause is kept in lenient mode, the formula stays unsatisfiable
+        let (phi, warnings) = Phi::from_reader_with_mode("p cnf 1 1\n0\n".as_bytes(), ParseMode::Lenient).unwrap();
+        assert_eq!(phi.clauses(), &[Clause::empty()]);
+        assert!(matches!(warnings.as_slice(), [Error::EmptyClause{..}, Error::HeaderMismatch{declared_vars: 1, vars: 0, ..}]));
+    }
+
+    #[test]
+    fn write_dimacs()
+    {
+        for path in ["TestData/uf50-01.cnf", "TestData/CBS_k3_n100_m449_b90_0.cnf"]
+        {
+            let phi = Phi::from_file(path).unwrap();
+            assert!(!phi.comments().is_empty());
+            let mut out = Vec::new();
+            phi.write_dimacs(&mut out, true).unwrap();
+            assert_eq!(Phi::from_reader(out.as_slice()).unwrap(), phi);
+
+            let mut out = Vec::new();
+            phi.write_dimacs(&mut out, false).unwrap();
+            let written = Phi::from_reader(out.as_slice()).unwrap();
+            assert!(written.comments().is_empty());
+            assert_eq!((written.clauses(), written.vars()), (phi.clauses(), phi.vars()));
+        }
+
+        //the unused variables declared by the header are kept
+        let (mut phi, _) = Phi::from_file_with_mode("TestData/mismatch.cnf", ParseMode::Lenient).unwrap();
+        phi.add_comment("first\nsecond");
+        let mut out = Vec::new();
+        phi.write_dimacs(&mut out, true).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("c first\nc second\n"));
+        assert!(text.contains("p cnf 4 2\n"));
+        let (written, _) = Phi::from_reader_with_mode(text.as_bytes(), ParseMode::Lenient).unwrap();
+        assert_eq!(written.vars(), 4);
+        assert_eq!(written.clauses(), phi.clauses());
+
+        let mut out = Vec::new();
+        Phi::from_reader("p cnf 0 0\n".as_bytes()).unwrap().write_dimacs(&mut out, false).unwrap();
+        assert_eq!(out, b"p cnf 0 0\n");
+    }
+
     #[test]
     fn from_file()
     {
@@ -336,6 +635,34 @@ mod tests
         assert_eq!(phi.clauses[2], Clause::new_c3(1,-2,3));
         let phi = Phi::from_file("TestData/test2.cnf");
         assert!(phi.is_err());
+        let phi = Phi::from_file("TestData/wide.cnf").unwrap();
+        assert_eq!(phi.clauses[1], Clause::new_cn(&[-1,2,-3,4,-5,6]));
+        assert_eq!(phi.max_clause_width(), 6);
+        assert!(!phi.is_3_sat());
+
+        let (phi, original_vars) = Phi::from_file_3sat("TestData/wide.cnf").unwrap();
+        assert_eq!(original_vars, 6);
+        assert!(phi.is_3_sat());
+        //a k-clause becomes k-2 clauses linked by k-3 new variables
+        assert_eq!(phi.clauses.len(), 3 + 4 + 1 + 3 + 1);
+        assert_eq!(phi.vars(), 6 + 2 + 3 + 2);
+    }
+
+    #[test]
+    fn from_reader()
+    {
+        let phi: Phi = "c clauses sharing and spanning lines\np cnf 4 3\n1 -2\t3 0 -4\n 2 0\n4 0\n%\n0\n".parse().unwrap();
+        assert_eq!(phi.clauses, vec![Clause::new_c3(1,-2,3), Clause::new_c2(-4,2), Clause::new_c1(4)]);
+        let (phi, original_vars) = Phi::from_reader_3sat("p cnf 5 1\n1 2 3 4 5 0\n".as_bytes()).unwrap();
+        assert_eq!((phi.clauses.len(), phi.vars(), original_vars), (3, 7, 5));
+
+        //the SATLIB files end with a % marker followed by a 0
+        for file in ["TestData/uf50-01.cnf", "TestData/uf250-05.cnf", "TestData/solver20-0.cnf"]
+        {
+            let contents = std::fs::read(file).unwrap();
+            assert_eq!(Phi::from_reader(contents.as_slice()).unwrap(), Phi::from_file(file).unwrap());
+        }
+        assert_eq!(Phi::from_file("TestData/uf50-01.cnf").unwrap().clauses.len(), 218);
     }
     #[test]
     fn phi_prime()
@@ -343,12 +670,53 @@ mod tests
         let c1 = Clause::new_c3(1,2,3);
         let c2 = Clause::new_c3(-2,-3,4);
         let c3 = Clause::new_c3(1,-2,3);
-        let phi = Phi{clauses: vec![c1,c2,c3]};
+        let phi = Phi::from_clauses(vec![c1.clone(),c2.clone(),c3.clone()]);
             
         let phi_p = phi.phi_prime(0);
         assert_eq!(phi_p.clauses[0], c1);
         assert_eq!(phi_p.clauses[1], c3);
         assert_eq!(phi_p.clauses.len(), 2);
+
+        let c4 = Clause::new_cn(&[-1,2,3,4,5]);
+        let phi = Phi::from_clauses(vec![c1.clone(),c2,c4.clone()]);
+        let phi_p = phi.phi_prime(0);
+        assert_eq!(phi_p.clauses, vec![c1,c4]);
+    }
+
+    #[test]
+    fn occurrences()
+    {
+        let mut phi = Phi::from_clauses(vec![
+            Clause::new_c3(1,-2,3),
+            Clause::new_c3(-1,-2,4),
+            Clause::new_c2(2,-3),
+        ]);
+        assert_eq!(phi.occurrences(0, true), &[0]);
+        assert_eq!(phi.occurrences(1, false), &[0,1]);
+        assert_eq!(phi.occurrences(3, false), &[] as &[usize]);
+        assert_eq!(phi.get_variables(), vec![0,1,2,3]);
+        assert_eq!(phi.create_unit(), vec![Clause::new_c1(4)]);
+
+        phi.add_clause(Clause::new_c2(-4,5));
+        assert_eq!(phi.occurrences(3, false), &[3]);
+        assert_eq!(phi.vars(), 5);
+        assert_eq!(phi.create_unit(), vec![Clause::new_c1(5)]);
+
+        let reduced = phi.reduce(&[Some(true), None, None, None, None]);
+        assert_eq!(reduced.clauses.len(), 3);
+        assert_eq!(reduced.occurrences(0, true), &[] as &[usize]);
+        assert_eq!(reduced.occurrences(1, true), &[1]);
+        assert_eq!(reduced.occurrences(3, false), &[2]);
+
+        phi.invert_literal(2);
+        assert_eq!(phi.occurrences(2, true), &[2]);
+        assert_eq!(phi.occurrences(2, false), &[0]);
+
+        //the clauses satisfied by -2 disappear from phi_prime
+        phi.update_implications(&Clause::new_c1(-2));
+        assert_eq!(phi.phi_prime(1).clauses.len(), 1);
+        assert_eq!(phi.phi_prime(1).occurrences(2, true), &[0]);
+        assert_eq!(phi.phi_prime(3).clauses, vec![Clause::new_c2(-4,5)]);
     }
 
     #[test]
@@ -357,7 +725,7 @@ mod tests
         let c1 = Clause::new_c3(1,2,3);
         let c2 = Clause::new_c3(2,3,4);
         let c3 = Clause::new_c3(1,-2,-3);
-        let phi = Phi{clauses: vec![c1,c2,c3]};
+        let phi = Phi::from_clauses(vec![c1,c2,c3]);
         let units = phi.create_unit();
         assert_eq!(units.len(), 2);
         assert_eq!(units[0], Clause::new_c1(1));
@@ -370,7 +738,7 @@ mod tests
         let c1 = Clause::new_c3(1,2,3);
         let c2 = Clause::new_c3(2,3,4);
         let c3 = Clause::new_c3(1,-2,-3);
-        let mut phi = Phi{clauses: vec![c1,c2,c3]};
+        let mut phi = Phi::from_clauses(vec![c1,c2,c3]);
         phi.invert_literal(1);
         assert_eq!(phi.clauses[0], Clause::new_c3(1,-2,3));
         assert_eq!(phi.clauses[1], Clause::new_c3(-2,3,4));
@@ -384,11 +752,11 @@ mod tests
         let l3 = Literal{index: 2, value: true, implicated: false, assigned: true};
         let l4 = Literal{index: 3, value: true, implicated: false, assigned: false};
 
-        let c1 = Clause::C3(l1,l2,l3);
-        let c2 = Clause::C3(l1,l2,l4);
-        let c3 = Clause::C2(l1,l2);
+        let c1 = Clause::new(vec![l1,l2,l3]);
+        let c2 = Clause::new(vec![l1,l2,l4]);
+        let c3 = Clause::new(vec![l1,l2]);
 
-        let phi = Phi{clauses: vec![c1,c2,c3]};
+        let phi = Phi::from_clauses(vec![c1.clone(),c2,c3.clone()]);
         let implications = phi.get_implications();
 
         assert_eq!(implications, vec![c1,c3]);
@@ -400,7 +768,7 @@ mod tests
         let c2 = Clause::new_c3(2,3,4);
         let c3 = Clause::new_c3(1,-2,-3);
         let c4 = Implication::new(1,2).to_clause();
-        let phi = Phi{clauses: vec![c1,c2,c3,c4]};
+        let phi = Phi::from_clauses(vec![c1,c2,c3,c4]);
         let implications = phi.get_added_clauses();
         assert_eq!(implications.len(), 1);
         assert_eq!(implications[0], Implication::new(1,2).to_clause());
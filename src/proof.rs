@@ -0,0 +1,729 @@
+use std::{collections::{HashMap, HashSet}, io::BufRead};
+use log::{debug, warn};
+use crate::{phi::Phi, clause::{Clause, Literal, words}, error::{Error, Position}, propagation::Propagator, drat::{Proof, ProofFormat}};
+
+/*
+Checker of proofs of unsatisfiability, for the machines where no external checker can be installed.
+Every lemma of a proof must be RUP: assigning the negation of its literals, unit propagation on the clauses before it
+finds a conflict. Otherwise it must be RAT on its first literal, the pivot: every resolvent with a clause
+containing the negated pivot must be RUP.
+DRAT proofs are checked backwards: a forward pass finds the clauses left after the step deriving the empty
+clause, then a single propagator goes back through the steps, dropping every lemma and adding back every deleted
+clause, and only the lemmas used by the conflicts of the checked ones are checked, each at a decision level undone
+afterwards.
+The lemmas and the clauses of the formula never used are trimmed away, what remains is an unsatisfiable core.
+LRAT proofs list the clauses that become unit for every lemma, they are checked forwards by following the hints.
+*/
+
+///The part of a formula and of its proof needed to derive the empty clause
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Core
+{
+    ///indices in phi of the clauses used by the proof, in ascending order
+    pub clauses: Vec<usize>,
+    ///lemmas used to derive the empty clause in proof order, the empty clause is the last one
+    pub lemmas: Vec<Clause>,
+}
+
+impl Core
+{
+    ///returns the unsatisfiable core, the formula made of the clauses used by the proof
+    pub fn to_phi(&self, phi: &Phi) -> Phi
+    {
+        Phi::from_clauses(self.clauses.iter().map(|i| phi.clauses()[*i].clone()).collect())
+    }
+
+    ///Writes the trimmed proof, it only contains the lemmas used to derive the empty clause
+    pub fn write_proof(&self, proof: &mut Proof)
+    {
+        for lemma in &self.lemmas
+        {
+            proof.add(&lemma.literals);
+        }
+    }
+}
+
+struct Step
+{
+    deletion: bool,
+    clause: Vec<Literal>
+}
+
+fn same(a: &Literal, b: &Literal) -> bool
+{
+    a.index == b.index && a.value == b.value
+}
+
+fn rejected(step: usize, reason: String) -> Error
+{
+    Error::ProofRejected{step, reason}
+}
+
+///Checks a DRAT proof of unsatisfiability of phi
+/// # Arguments
+/// * `phi` - The formula refuted by the proof
+/// * `reader` - The proof, in text or binary format
+/// # Returns
+/// * The clauses and the lemmas used by the proof, or the first step that does not follow from the ones before it
+/// # Example
+/// ```
+/// use colombini_sat::{phi::Phi, clause::Clause, drat::ProofFormat, proof::check_drat};
+///
+/// let phi = Phi::from_clauses(vec![Clause::new_c2(1,2), Clause::new_c2(-1,2), Clause::new_c2(1,-2), Clause::new_c2(-1,-2), Clause::new_c1(3)]);
+/// let core = check_drat(&phi, "2 0\n0\n".as_bytes(), ProofFormat::Text).unwrap();
+/// assert_eq!(core.clauses, vec![0, 1, 2, 3]);
+/// assert!(check_drat(&phi, "0\n".as_bytes(), ProofFormat::Text).is_err());
+/// ```
+pub fn check_drat<R: BufRead>(phi: &Phi, reader: R, format: ProofFormat) -> Result<Core, Error>
+{
+    let steps = match format
+    {
+        ProofFormat::Text => parse_text(reader)?,
+        ProofFormat::Binary => parse_binary(reader)?
+    };
+    let mut checker = Drat::new(phi, steps);
+    checker.check()?;
+    let core = checker.core();
+    debug!("proof checked: {} of {} clauses and {} of {} lemmas in the core",
+        core.clauses.len(), phi.clauses().len(), core.lemmas.len(), checker.clauses.len() - checker.originals);
+    Ok(core)
+}
+
+fn parse_text<R: BufRead>(reader: R) -> Result<Vec<Step>, Error>
+{
+    let mut steps = Vec::new();
+    let mut pending: Vec<Literal> = Vec::new();
+    let mut deletion = false;
+    let mut start = Position::new(1, 1);
+    for (number, line) in reader.lines().enumerate()
+    {
+        let line = line.map_err(|source| Error::Io{at: Some(Position::new(number + 1, 1)), source})?;
+        for (column, word) in words(&line)
+        {
+            let at = Position::new(number + 1, column);
+            let opening = pending.is_empty() && !deletion;
+            if opening && word.starts_with('c') { break; }
+            if opening { start = at; }
+            if opening && word == "d"
+            {
+                deletion = true;
+                continue;
+            }
+            let value: isize = word.parse().map_err(|_| Error::BadLiteral{at, token: word.to_string()})?;
+            if value != 0 { pending.push(Literal::from_isize(value)); }
+            else
+            {
+                steps.push(Step{deletion, clause: std::mem::take(&mut pending)});
+                deletion = false;
+            }
+        }
+    }
+    if !pending.is_empty() || deletion { return Err(Error::UnterminatedClause{at: start}); }
+    Ok(steps)
+}
+
+fn parse_binary<R: BufRead>(reader: R) -> Result<Vec<Step>, Error>
+{
+    let mut steps = Vec::new();
+    let mut bytes = reader.bytes();
+    while let Some(kind) = bytes.next()
+    {
+        let step = steps.len() + 1;
+        let deletion = match kind?
+        {
+            b'a' => false,
+            b'd' => true,
+            other => return Err(rejected(step, format!("unexpected byte {other:#04x}, expected 'a' or 'd'")))
+        };
+        let mut clause = Vec::new();
+        loop
+        {
+            //literals are variable length integers, 7 bits per byte starting from the lowest ones
+            let mut code: u64 = 0;
+            let mut shift = 0;
+            loop
+            {
+                let byte = bytes.next().ok_or_else(|| rejected(step, "clause not terminated by 0".to_string()))??;
+                if shift > 56 { return Err(rejected(step, "literal out of range".to_string())); }
+                code |= ((byte & 127) as u64) << shift;
+                shift += 7;
+                if byte & 128 == 0 { break; }
+            }
+            match code
+            {
+                0 => break,
+                1 => return Err(rejected(step, "literal out of range".to_string())),
+                _ => clause.push(Literal{index: (code / 2 - 1) as usize, value: code.is_multiple_of(2), implicated: false, assigned: false})
+            }
+        }
+        steps.push(Step{deletion, clause});
+    }
+    Ok(steps)
+}
+
+struct Drat
+{
+    //the clauses of the formula followed by the lemmas
+    clauses: Vec<Vec<Literal>>,
+    originals: usize,
+    //the clause added or deleted by every step, None for the deletions of missing clauses
+    steps: Vec<(bool, Option<usize>)>,
+    n_vars: usize,
+    core: Vec<bool>,
+    //the clauses active at the step being checked, at first the ones left after the last step
+    active: Vec<bool>,
+    //the clauses containing every literal, by 2*variable + value, to find the candidates of RAT
+    occurrences: Vec<Vec<usize>>,
+    //the active clauses with two literals or more, the units and the empty clause are assigned by every check
+    propagator: Propagator,
+    slots: Vec<Option<usize>>,
+    ids: Vec<usize>,
+    units: Vec<usize>,
+    //the unit clause that assigned every variable in the current check
+    unit_reasons: Vec<Option<usize>>
+}
+
+impl Drat
+{
+    fn new(phi: &Phi, steps: Vec<Step>) -> Drat
+    {
+        let originals = phi.clauses().len();
+        let mut checker = Drat{
+            clauses: phi.clauses().iter().map(|c| c.literals.clone()).collect(),
+            originals,
+            steps: Vec::with_capacity(steps.len()),
+            n_vars: phi.vars(),
+            core: Vec::new(),
+            active: vec![true; originals],
+            occurrences: Vec::new(),
+            propagator: Propagator::new(&Phi::new(), 0),
+            slots: Vec::new(),
+            ids: Vec::new(),
+            units: Vec::new(),
+            unit_reasons: Vec::new()
+        };
+        //the active clauses with the same literals, to find the ones deleted
+        let mut active: HashMap<Vec<isize>, Vec<usize>> = HashMap::new();
+        for (id, clause) in checker.clauses.iter().enumerate()
+        {
+            active.entry(Drat::key(clause)).or_default().push(id);
+        }
+        for (s, step) in steps.into_iter().enumerate()
+        {
+            if let Some(max) = step.clause.iter().map(|l| l.index + 1).max() { checker.n_vars = checker.n_vars.max(max); }
+            let key = Drat::key(&step.clause);
+            if step.deletion
+            {
+                let id = active.get_mut(&key).and_then(|ids| ids.pop());
+                match id
+                {
+                    Some(id) => checker.active[id] = false,
+                    None => warn!("proof step {}: deleted clause {} not found, ignored", s + 1, Clause::new(step.clause))
+                }
+                checker.steps.push((true, id));
+            }
+            else
+            {
+                let id = checker.clauses.len();
+                let empty = step.clause.is_empty();
+                checker.clauses.push(step.clause);
+                checker.active.push(true);
+                active.entry(key).or_default().push(id);
+                checker.steps.push((false, Some(id)));
+                //the steps after the empty clause are not needed
+                if empty { break; }
+            }
+        }
+        let n = checker.clauses.len();
+        checker.core = vec![false; n];
+        checker.slots = vec![None; n];
+        checker.occurrences = vec![Vec::new(); 2*checker.n_vars];
+        for (id, clause) in checker.clauses.iter().enumerate()
+        {
+            for lit in clause
+            {
+                checker.occurrences[2*lit.index + lit.value as usize].push(id);
+            }
+        }
+        checker.propagator = Propagator::new(&Phi::new(), checker.n_vars);
+        checker.unit_reasons = vec![None; checker.n_vars];
+        for id in 0..n
+        {
+            if checker.active[id] { checker.insert(id); }
+        }
+        checker
+    }
+
+    fn key(clause: &[Literal]) -> Vec<isize>
+    {
+        let mut key: Vec<isize> = clause.iter().map(|l| l.as_isize()).collect();
+        key.sort_unstable();
+        key.dedup();
+        key
+    }
+
+    ///adds an active clause to the propagator, or to the units
+    fn insert(&mut self, id: usize)
+    {
+        let mut literals: Vec<Literal> = Vec::with_capacity(self.clauses[id].len());
+        for lit in &self.clauses[id]
+        {
+            if !literals.iter().any(|l| same(l, lit)) { literals.push(*lit); }
+        }
+        if literals.len() < 2
+        {
+            self.units.push(id);
+            return;
+        }
+        //nothing is assigned between two checks, so the watched literals are never false
+        let slot = self.propagator.add_clause(literals);
+        if self.ids.len() <= slot { self.ids.resize(slot + 1, 0); }
+        self.ids[slot] = id;
+        self.slots[id] = Some(slot);
+    }
+
+    ///the clauses deleted by a step are active again before it, the lemmas are not
+    fn set_active(&mut self, id: usize, active: bool)
+    {
+        if self.active[id] == active { return; }
+        self.active[id] = active;
+        if active { self.insert(id); }
+        else
+        {
+            match self.slots[id].take()
+            {
+                Some(slot) => self.propagator.delete_clauses(&[slot]),
+                None => self.units.retain(|u| *u != id)
+            }
+        }
+    }
+
+    fn check(&mut self) -> Result<(), Error>
+    {
+        let derives_empty = matches!(self.steps.last(), Some((false, Some(id)))
+            if self.clauses[*id].is_empty());
+        if derives_empty
+        {
+            let id = self.clauses.len() - 1;
+            self.core[id] = true;
+        }
+        else
+        {
+            //without the empty clause, the lemmas must lead to a conflict by themselves
+            let last = self.steps.len();
+            let used = self.rup(&[]).ok_or_else(|| rejected(last, "the proof does not derive the empty clause".to_string()))?;
+            for id in used { self.core[id] = true; }
+        }
+        //a single propagator goes backwards through the steps, the lemmas are removed before being checked
+        //and the deleted clauses are added back
+        for s in (0..self.steps.len()).rev()
+        {
+            match self.steps[s]
+            {
+                (false, Some(id)) => {
+                    self.set_active(id, false);
+                    if self.core[id] { self.check_lemma(id, s)?; }
+                },
+                (true, Some(id)) => self.set_active(id, true),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    ///checks that the lemma added at the given step is RUP or RAT, marking the clauses it needs as part of the core
+    fn check_lemma(&mut self, id: usize, step: usize) -> Result<(), Error>
+    {
+        let lemma = self.clauses[id].clone();
+        if let Some(used) = self.rup(&lemma)
+        {
+            for c in used { self.core[c] = true; }
+            return Ok(());
+        }
+        let pivot = match lemma.first()
+        {
+            Some(pivot) => pivot.negated(),
+            None => return Err(rejected(step + 1, "the empty clause is not implied by unit propagation".to_string()))
+        };
+        let candidates: Vec<usize> = self.occurrences[2*pivot.index + pivot.value as usize].iter().copied().filter(|c| self.active[*c]).collect();
+        for candidate in candidates
+        {
+            let mut resolvent = lemma.clone();
+            let mut tautology = false;
+            for l in self.clauses[candidate].iter().filter(|l| !same(l, &pivot))
+            {
+                if resolvent.iter().any(|r| same(r, &l.negated())) { tautology = true; }
+                if !resolvent.iter().any(|r| same(r, l)) { resolvent.push(*l); }
+            }
+            if tautology { continue; }
+            match self.rup(&resolvent)
+            {
+                Some(used) => {
+                    self.core[candidate] = true;
+                    for c in used { self.core[c] = true; }
+                },
+                None => return Err(rejected(step + 1, format!("the lemma {} is neither RUP nor RAT", Clause::new(lemma))))
+            }
+        }
+        Ok(())
+    }
+
+    ///returns the clause that assigned the variable in the current check, None for the negated literals
+    fn reason(&self, index: usize) -> Option<usize>
+    {
+        self.propagator.reason(index).map(|slot| self.ids[slot]).or(self.unit_reasons[index])
+    }
+
+    ///Propagates the negation of the literals on the active clauses, in a decision level undone afterwards
+    /// # Returns
+    /// * The clauses that take part in the conflict, None if there is no conflict
+    fn rup(&mut self, literals: &[Literal]) -> Option<Vec<usize>>
+    {
+        //the first assignment opens the level of the check
+        fn set(propagator: &mut Propagator, lit: Literal) -> bool
+        {
+            if propagator.decision_level() == 0
+            {
+                propagator.decide(lit);
+                true
+            }
+            else { propagator.assign(lit, None) }
+        }
+        let mut roots: Vec<usize> = Vec::new();
+        let mut assigned: Vec<usize> = Vec::new();
+        let mut conflict = false;
+        for u in self.units.clone()
+        {
+            match self.clauses[u].first()
+            {
+                None => roots.push(u),
+                Some(lit) => {
+                    if self.propagator.value(lit.index).is_none()
+                    {
+                        set(&mut self.propagator, *lit);
+                        self.unit_reasons[lit.index] = Some(u);
+                        assigned.push(lit.index);
+                        continue;
+                    }
+                    if self.propagator.value(lit.index) == Some(lit.value) { continue; }
+                    roots.push(u);
+                    roots.extend(self.unit_reasons[lit.index]);
+                }
+            }
+            conflict = true;
+            break;
+        }
+        if !conflict
+        {
+            for lit in literals
+            {
+                //the literal is already true, by a unit clause or because the lemma is a tautology
+                if !set(&mut self.propagator, lit.negated())
+                {
+                    roots.extend(self.reason(lit.index));
+                    conflict = true;
+                    break;
+                }
+            }
+        }
+        if !conflict
+        {
+            match self.propagator.propagate()
+            {
+                Some(slot) => roots.push(self.ids[slot]),
+                None => {
+                    self.undo(&assigned);
+                    return None;
+                }
+            }
+        }
+        //walk the reasons back from the conflict
+        let mut used: HashSet<usize> = HashSet::new();
+        while let Some(c) = roots.pop()
+        {
+            if !used.insert(c) { continue; }
+            for l in &self.clauses[c]
+            {
+                roots.extend(self.reason(l.index));
+            }
+        }
+        self.undo(&assigned);
+        Some(used.into_iter().collect())
+    }
+
+    fn undo(&mut self, assigned: &[usize])
+    {
+        self.propagator.backtrack(0);
+        for index in assigned
+        {
+            self.unit_reasons[*index] = None;
+        }
+    }
+
+    fn core(&self) -> Core
+    {
+        Core{
+            clauses: (0..self.originals).filter(|id| self.core[*id]).collect(),
+            lemmas: (self.originals..self.clauses.len()).filter(|id| self.core[*id]).map(|id| Clause::new(self.clauses[id].clone())).collect()
+        }
+    }
+}
+
+///Checks a LRAT proof of unsatisfiability of phi, the clauses of phi have the identifiers from 1 onwards
+/// # Arguments
+/// * `phi` - The formula refuted by the proof
+/// * `reader` - The proof in text format, every lemma is followed by the identifiers of the clauses that become unit
+/// # Returns
+/// * The clauses and the lemmas used by the proof, or the first step that does not follow from the ones before it
+/// # Example
+/// ```
+/// use colombini_sat::{phi::Phi, clause::Clause, proof::check_lrat};
+///
+/// let phi = Phi::from_clauses(vec![Clause::new_c2(1,2), Clause::new_c2(-1,2), Clause::new_c2(1,-2), Clause::new_c2(-1,-2)]);
+/// let core = check_lrat(&phi, "5 2 0 1 2 0\n6 0 5 3 4 0\n".as_bytes()).unwrap();
+/// assert_eq!(core.lemmas.len(), 2);
+/// ```
+pub fn check_lrat<R: BufRead>(phi: &Phi, reader: R) -> Result<Core, Error>
+{
+    let mut clauses: HashMap<usize, Clause> = phi.clauses().iter().enumerate().map(|(i, c)| (i + 1, c.clone())).collect();
+    //the hints used by every lemma, to find the core
+    let mut lemmas: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut order: Vec<(usize, Clause)> = Vec::new();
+    let mut n_vars = phi.vars();
+    let mut empty = None;
+    for (number, line) in reader.lines().enumerate()
+    {
+        let step = number + 1;
+        let line = line.map_err(|source| Error::Io{at: Some(Position::new(step, 1)), source})?;
+        let words = words(&line);
+        if words.first().is_none_or(|(_, w)| w.starts_with('c')) { continue; }
+        let mut numbers: Vec<isize> = Vec::with_capacity(words.len());
+        let mut deletion = false;
+        for (i, (column, word)) in words.iter().enumerate()
+        {
+            if i == 1 && *word == "d" { deletion = true; continue; }
+            numbers.push(word.parse().map_err(|_| Error::BadLiteral{at: Position::new(step, *column), token: word.to_string()})?);
+        }
+        let id = match numbers.first()
+        {
+            Some(id) if *id > 0 => *id as usize,
+            _ => return Err(rejected(step, "a step must start with a positive identifier".to_string()))
+        };
+        //the literals end at the first 0 and the hints at the second one
+        let zeros: Vec<usize> = numbers.iter().enumerate().filter(|(_, n)| **n == 0).map(|(i, _)| i).collect();
+        if deletion
+        {
+            if zeros.first() != Some(&(numbers.len() - 1)) { return Err(Error::UnterminatedClause{at: Position::new(step, words[0].0)}); }
+            for deleted in &numbers[1..numbers.len() - 1]
+            {
+                if clauses.remove(&deleted.unsigned_abs()).is_none() { warn!("proof step {step}: deleted clause {deleted} not found, ignored"); }
+            }
+            continue;
+        }
+        if zeros.len() != 2 || zeros[1] != numbers.len() - 1 { return Err(Error::UnterminatedClause{at: Position::new(step, words[0].0)}); }
+        let literals: Vec<Literal> = numbers[1..zeros[0]].iter().map(|l| Literal::from_isize(*l)).collect();
+        let hints = &numbers[zeros[0] + 1..zeros[1]];
+        if clauses.contains_key(&id) || lemmas.contains_key(&id) || id <= phi.clauses().len()
+        {
+            return Err(rejected(step, format!("the identifier {id} is already used")));
+        }
+        if let Some(max) = literals.iter().map(|l| l.index + 1).max() { n_vars = n_vars.max(max); }
+
+        let used = check_lrat_lemma(&clauses, &literals, hints, n_vars).map_err(|reason| rejected(step, reason))?;
+        let lemma = Clause::new(literals);
+        lemmas.insert(id, used);
+        order.push((id, lemma.clone()));
+        if lemma.is_empty()
+        {
+            empty = Some(id);
+            break;
+        }
+        clauses.insert(id, lemma);
+    }
+    let empty = empty.ok_or_else(|| rejected(order.len(), "the proof does not derive the empty clause".to_string()))?;
+
+    //follow the hints back from the empty clause
+    let mut used: Vec<usize> = vec![empty];
+    let mut core_lemmas: HashMap<usize, bool> = HashMap::new();
+    let mut core_clauses: Vec<bool> = vec![false; phi.clauses().len()];
+    while let Some(id) = used.pop()
+    {
+        match lemmas.get(&id)
+        {
+            Some(hints) => {
+                if core_lemmas.insert(id, true).is_none() { used.extend(hints); }
+            },
+            None => core_clauses[id - 1] = true
+        }
+    }
+    Ok(Core{
+        clauses: core_clauses.iter().enumerate().filter(|(_, c)| **c).map(|(i, _)| i).collect(),
+        lemmas: order.into_iter().filter(|(id, _)| core_lemmas.contains_key(id)).map(|(_, lemma)| lemma).collect()
+    })
+}
+
+///Checks a LRAT lemma: the hints before the first negative one must lead to a conflict,
+///otherwise every clause with the negated pivot is resolved with the lemma and checked with the hints after its negated identifier
+/// # Returns
+/// * The identifiers of the clauses the check used, hints after a conflict are left out
+fn check_lrat_lemma(clauses: &HashMap<usize, Clause>, literals: &[Literal], hints: &[isize], n_vars: usize) -> Result<Vec<usize>, String>
+{
+    let mut used = Vec::new();
+    let mut values: Vec<Option<bool>> = vec![None; n_vars];
+    for lit in literals
+    {
+        //a lemma with opposite literals is always true
+        if values[lit.index] == Some(lit.value) { return Ok(used); }
+        values[lit.index] = Some(!lit.value);
+    }
+    let split = hints.iter().position(|h| *h < 0).unwrap_or(hints.len());
+    if unit_chain(clauses, &mut values, &hints[..split], &mut used)? { return Ok(used); }
+
+    let pivot = match literals.first()
+    {
+        Some(pivot) => pivot.negated(),
+        None => return Err("the empty clause is not implied by its hints".to_string())
+    };
+    //the hints of every clause resolved with the lemma
+    let mut groups: HashMap<usize, &[isize]> = HashMap::new();
+    let mut rest = &hints[split..];
+    while let Some((first, tail)) = rest.split_first()
+    {
+        let end = tail.iter().position(|h| *h < 0).unwrap_or(tail.len());
+        groups.insert(first.unsigned_abs(), &tail[..end]);
+        rest = &tail[end..];
+    }
+    let mut candidates: Vec<(&usize, &Clause)> = clauses.iter().filter(|(_, c)| c.literals.iter().any(|l| same(l, &pivot))).collect();
+    candidates.sort_unstable_by_key(|(id, _)| **id);
+    for (id, clause) in candidates
+    {
+        let others: Vec<&Literal> = clause.literals.iter().filter(|l| !same(l, &pivot)).collect();
+        //the resolvent is a tautology
+        if others.iter().any(|l| values[l.index] == Some(l.value)) { continue; }
+        let group = groups.get(id).ok_or_else(|| format!("no hints for the clause {id}, that contains the negated pivot"))?;
+        let mut resolved = values.clone();
+        for l in others
+        {
+            resolved[l.index] = Some(!l.value);
+        }
+        used.push(*id);
+        if !unit_chain(clauses, &mut resolved, group, &mut used)?
+        {
+            return Err(format!("the hints of the clause {id} do not lead to a conflict"));
+        }
+    }
+    Ok(used)
+}
+
+///Assigns the literal left by every hint, reducing it on the values, and adds the hints it reads to used
+/// # Returns
+/// * true if a hint is falsified, an error if a hint is satisfied or has more than one literal left
+fn unit_chain(clauses: &HashMap<usize, Clause>, values: &mut [Option<bool>], hints: &[isize], used: &mut Vec<usize>) -> Result<bool, String>
+{
+    for hint in hints
+    {
+        let clause = clauses.get(&hint.unsigned_abs()).ok_or_else(|| format!("the hint {hint} is not an active clause"))?;
+        used.push(hint.unsigned_abs());
+        let reduced = clause.reduce(values).ok_or_else(|| format!("the hint {hint} is satisfied"))?;
+        let mut left = reduced.literals.iter().filter(|l| !l.assigned);
+        match (left.next(), left.next())
+        {
+            (None, _) => return Ok(true),
+            (Some(lit), None) => values[lit.index] = Some(lit.value),
+            _ => return Err(format!("the hint {hint} is not unit"))
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{phi::Phi, clause::Clause, error::Error, drat::ProofFormat};
+    use super::{check_drat, check_lrat};
+
+    //every clause over three variables
+    fn full() -> Phi
+    {
+        let mut clauses = Vec::new();
+        for a in [1,-1]{
+            for b in [2,-2]{
+                for c in [3,-3]{
+                    clauses.push(Clause::new_c3(a,b,c));
+                }
+            }
+        }
+        Phi::from_clauses(clauses)
+    }
+
+    //a formula where the lemma 1 is RAT but not RUP, and the lemma 2 needs it
+    fn rat() -> Phi
+    {
+        Phi::from_clauses(vec![
+            Clause::new_c3(-4,-3,-2), Clause::new_c2(4,-3), Clause::new_c3(3,4,2), Clause::new_c3(4,3,-1),
+            Clause::new_c3(3,-4,1), Clause::new_c3(4,-2,1), Clause::new_c3(1,2,-3), Clause::new_c2(-4,-1)
+        ])
+    }
+
+    #[test]
+    fn drat()
+    {
+        let core = check_drat(&full(), "1 2 0\n1 0\nc comment\n2 0 0\n".as_bytes(), ProofFormat::Text).unwrap();
+        assert_eq!(core.clauses, (0..8).collect::<Vec<usize>>());
+        assert_eq!(core.lemmas, vec![Clause::new_c2(1,2), Clause::new_c1(1), Clause::new_c1(2), Clause::empty()]);
+
+        //the same proof in binary
+        let binary = [b'a', 2, 4, 0, b'a', 2, 0, b'a', 4, 0, b'a', 0];
+        assert_eq!(check_drat(&full(), &binary[..], ProofFormat::Binary).unwrap(), core);
+
+        //unused lemmas and clauses are trimmed, deletions and the steps after the empty clause are ignored
+        let mut phi = full();
+        phi.add_clause(Clause::new_c3(4,5,6));
+        let core = check_drat(&phi, "4 5 0\n1 2 0\nd 4 5 0\n1 0\n2 0\n0\n-7 0\n".as_bytes(), ProofFormat::Text).unwrap();
+        assert_eq!(core.clauses.len(), 8);
+        assert_eq!(core.lemmas.len(), 4);
+
+        let core = check_drat(&rat(), "1 0\n2 0\n0\n".as_bytes(), ProofFormat::Text).unwrap();
+        //the lemma 1 is only RAT, once it is known the empty clause does not need the lemma 2
+        assert_eq!(core.lemmas, vec![Clause::new_c1(1), Clause::empty()]);
+    }
+
+    #[test]
+    fn drat_rejected()
+    {
+        //the empty clause needs a lemma that was deleted
+        let proof = "1 2 0\n1 0\n2 0\nd 2 0\n0\n";
+        assert!(matches!(check_drat(&full(), proof.as_bytes(), ProofFormat::Text), Err(Error::ProofRejected{step: 5, ..})));
+        let phi = Phi::from_clauses(vec![Clause::new_c2(1,2), Clause::new_c2(-1,2)]);
+        assert!(matches!(check_drat(&phi, "-2 0\n0\n".as_bytes(), ProofFormat::Text), Err(Error::ProofRejected{step: 1, ..})));
+        assert!(matches!(check_drat(&full(), "1 2 0\n".as_bytes(), ProofFormat::Text), Err(Error::ProofRejected{step: 1, ..})));
+        assert!(matches!(check_drat(&full(), "1 x 0\n".as_bytes(), ProofFormat::Text), Err(Error::BadLiteral{..})));
+        assert!(matches!(check_drat(&full(), "1 2\n".as_bytes(), ProofFormat::Text), Err(Error::UnterminatedClause{..})));
+        assert!(check_drat(&full(), &[b'a', 2][..], ProofFormat::Binary).is_err());
+    }
+
+    #[test]
+    fn lrat()
+    {
+        let proof = "9 1 2 0 1 2 0\n10 1 0 9 3 4 0\n10 d 9 0\n11 2 0 10 5 6 0\n12 0 10 11 7 8 0\n";
+        let core = check_lrat(&full(), proof.as_bytes()).unwrap();
+        assert_eq!(core.clauses.len(), 8);
+        assert_eq!(core.lemmas.len(), 4);
+        //the hints after the conflict are not read
+        let proof = "9 1 2 0 1 2 0\n10 1 0 9 3 4 0\n10 d 9 0\n11 2 0 10 5 6 0\n12 0 10 11 7 8 12345 0\n";
+        assert_eq!(check_lrat(&full(), proof.as_bytes()).unwrap(), core);
+
+        let proof = "9 1 0 -4 3 6 -8 5 1 7 0\n10 2 0 9 8 2 3 0\n11 0 9 10 8 2 4 0\n";
+        let core = check_lrat(&rat(), proof.as_bytes()).unwrap();
+        assert_eq!(core.clauses, (0..8).collect::<Vec<usize>>());
+
+        //a hint that is not unit, a missing group for a clause with the negated pivot, a deleted hint
+        assert!(matches!(check_lrat(&full(), "9 1 2 0 1 2 0\n10 1 0 3 4 0\n".as_bytes()), Err(Error::ProofRejected{step: 2, ..})));
+        assert!(matches!(check_lrat(&rat(), "9 1 0 -4 3 6 0\n".as_bytes()), Err(Error::ProofRejected{step: 1, ..})));
+        assert!(matches!(check_lrat(&full(), "9 1 2 0 1 2 0\n9 d 9 0\n10 1 0 9 3 4 0\n".as_bytes()), Err(Error::ProofRejected{step: 3, ..})));
+        assert!(matches!(check_lrat(&full(), "9 1 2 0 1 2 0\n".as_bytes()), Err(Error::ProofRejected{..})));
+        //an identifier of the formula, an unknown hint before the conflict
+        assert!(matches!(check_lrat(&full(), "8 1 2 0 1 2 0\n".as_bytes()), Err(Error::ProofRejected{step: 1, ..})));
+        assert!(matches!(check_lrat(&full(), "9 1 2 0 12345 1 2 0\n".as_bytes()), Err(Error::ProofRejected{step: 1, ..})));
+    }
+}
@@ -0,0 +1,427 @@
+use crate::{phi::Phi, clause::Literal};
+
+/*
+Unit propagation with two watched literals per clause.
+The first two literals of every clause are the watched ones: as long as neither of them is false the clause
+can not be unit, so a clause is only visited when one of its watched literals becomes false.
+Assignments are recorded on a trail split in decision levels, backtracking pops the trail in place.
+*/
+
+pub struct Propagator
+{
+    clauses: Vec<Vec<Literal>>,
+    //indices of the deleted clauses, reused by the next clauses added
+    free: Vec<usize>,
+    //for every literal, the clauses that watch it
+    watches: Vec<Vec<usize>>,
+    assignments: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    //the clause that implied each variable, None for decisions
+    reasons: Vec<Option<usize>>,
+    trail: Vec<Literal>,
+    //position in the trail where each decision level starts
+    trail_limits: Vec<usize>,
+    //position in the trail of the next literal to propagate
+    propagated: usize,
+    //the value every variable had when it was last unassigned
+    saved_phases: Vec<Option<bool>>,
+    //a clause that was already falsified when it was added
+    falsified: Option<usize>,
+    //number of literals propagated since the creation
+    propagations: u64
+}
+
+fn code(lit: &Literal) -> usize
+{
+    2*lit.index + lit.value as usize
+}
+
+fn negated_code(lit: &Literal) -> usize
+{
+    2*lit.index + !lit.value as usize
+}
+
+impl Propagator
+{
+    ///Creates a propagator for the clauses of phi, clauses with implicated literals are skipped
+    ///and assigned literals are considered false
+    /// # Arguments
+    /// * `phi` - The formula to propagate
+    /// * `n_vars` - The number of variables, if phi uses more variables the bigger number is used
+    pub fn new(phi: &Phi, n_vars: usize) -> Propagator
+    {
+        let n_vars = n_vars.max(phi.vars());
+        let mut propagator = Propagator{
+            clauses: Vec::with_capacity(phi.clauses().len()),
+            free: Vec::new(),
+            watches: vec![Vec::new(); 2*n_vars],
+            assignments: vec![None; n_vars],
+            levels: vec![0; n_vars],
+            reasons: vec![None; n_vars],
+            trail: Vec::with_capacity(n_vars),
+            trail_limits: Vec::new(),
+            propagated: 0,
+            saved_phases: vec![None; n_vars],
+            falsified: None,
+            propagations: 0
+        };
+        for clause in phi.clauses()
+        {
+            if let Some(clause) = clause.adapt()
+            {
+                propagator.add_clause(clause.literals);
+            }
+        }
+        propagator
+    }
+
+    pub fn num_vars(&self) -> usize
+    {
+        self.assignments.len()
+    }
+
+    ///returns the number of clauses, the deleted ones excluded
+    pub fn num_clauses(&self) -> usize
+    {
+        self.clauses.len() - self.free.len()
+    }
+
+    pub fn assignments(&self) -> &[Option<bool>]
+    {
+        &self.assignments
+    }
+
+    pub fn value(&self, index: usize) -> Option<bool>
+    {
+        self.assignments[index]
+    }
+
+    pub fn level(&self, index: usize) -> usize
+    {
+        self.levels[index]
+    }
+
+    pub fn reason(&self, index: usize) -> Option<usize>
+    {
+        self.reasons[index]
+    }
+
+    pub fn clause(&self, index: usize) -> &[Literal]
+    {
+        &self.clauses[index]
+    }
+
+    ///returns the indices of the clauses watching the literal.\
+    ///After a propagation without conflicts, a clause that is not satisfied is watched by two unassigned literals.
+    pub fn watchers(&self, lit: &Literal) -> &[usize]
+    {
+        &self.watches[code(lit)]
+    }
+
+    pub fn trail(&self) -> &[Literal]
+    {
+        &self.trail
+    }
+
+    pub fn decision_level(&self) -> usize
+    {
+        self.trail_limits.len()
+    }
+
+    ///returns the decision of every level, from the first one
+    pub fn decisions(&self) -> impl Iterator<Item = Literal> + '_
+    {
+        self.trail_limits.iter().map(|position| self.trail[*position])
+    }
+
+    ///returns the number of literals propagated since the propagator was created
+    pub fn propagations(&self) -> u64
+    {
+        self.propagations
+    }
+
+    ///returns the value the variable had when it was last unassigned by a backtrack
+    pub fn saved_phase(&self, index: usize) -> Option<bool>
+    {
+        self.saved_phases[index]
+    }
+
+    fn lit_value(&self, lit: &Literal) -> Option<bool>
+    {
+        self.assignments[lit.index].map(|v| v == lit.value)
+    }
+
+    ///Adds unassigned variables until there are n_vars of them, used when clauses over new variables are added
+    pub fn add_vars(&mut self, n_vars: usize)
+    {
+        if n_vars <= self.num_vars() { return; }
+        self.watches.resize(2*n_vars, Vec::new());
+        self.assignments.resize(n_vars, None);
+        self.levels.resize(n_vars, 0);
+        self.reasons.resize(n_vars, None);
+        self.saved_phases.resize(n_vars, None);
+    }
+
+    ///Adds a clause watching its first two literals, duplicated literals are removed.\
+    ///A unit clause is assigned right away, an empty or falsified unit clause is reported by the next propagation.
+    /// # Returns
+    /// * The index of the clause
+    pub fn add_clause(&mut self, mut literals: Vec<Literal>) -> usize
+    {
+        let index = self.free.pop().unwrap_or(self.clauses.len());
+        let mut i = 0;
+        while i < literals.len()
+        {
+            if literals[..i].iter().any(|l| l.index == literals[i].index && l.value == literals[i].value) { literals.remove(i); }
+            else { i += 1; }
+        }
+        match literals.len()
+        {
+            0 => { self.falsified = self.falsified.or(Some(index)); },
+            1 => {
+                if !self.assign(literals[0], Some(index)) { self.falsified = self.falsified.or(Some(index)); }
+            },
+            _ => {
+                self.watches[code(&literals[0])].push(index);
+                self.watches[code(&literals[1])].push(index);
+            }
+        }
+        if index == self.clauses.len() { self.clauses.push(literals); }
+        else { self.clauses[index] = literals; }
+        index
+    }
+
+    ///Adds a clause implied by the others in the middle of a search, the watched literals are the ones that are
+    ///not false or else the false ones assigned last. If the other literals are false the first one is assigned.
+    /// # Returns
+    /// * The index of the clause
+    pub fn add_implied(&mut self, mut literals: Vec<Literal>) -> usize
+    {
+        literals.sort_by_key(|l| match self.lit_value(l)
+        {
+            Some(false) => (true, usize::MAX - self.levels[l.index]),
+            _ => (false, 0)
+        });
+        let unit = literals.len() > 1 && self.lit_value(&literals[0]).is_none() && self.lit_value(&literals[1]) == Some(false);
+        let first = literals.first().copied();
+        let index = self.add_clause(literals);
+        if let (true, Some(first)) = (unit, first) { self.assign(first, Some(index)); }
+        index
+    }
+
+    ///returns true if the clause is the reason of an assignment, it can not be deleted until that is undone
+    pub fn is_reason(&self, index: usize) -> bool
+    {
+        match self.clauses[index].first()
+        {
+            Some(first) => self.reasons[first.index] == Some(index),
+            None => false
+        }
+    }
+
+    ///Deletes clauses that are not the reason of any assignment, their indices are reused by the next clauses added
+    pub fn delete_clauses(&mut self, indices: &[usize])
+    {
+        for index in indices
+        {
+            debug_assert!(!self.is_reason(*index));
+            //a clause is only watched by its first two literals
+            let literals = std::mem::take(&mut self.clauses[*index]);
+            for lit in literals.iter().take(2)
+            {
+                self.watches[code(lit)].retain(|c| c != index);
+            }
+            self.free.push(*index);
+        }
+    }
+
+    ///Opens a new decision level and assigns the literal in it
+    pub fn decide(&mut self, lit: Literal)
+    {
+        self.trail_limits.push(self.trail.len());
+        self.assign(lit, None);
+    }
+
+    ///Assigns the literal at the current decision level
+    /// # Returns
+    /// * false if the variable was already assigned to the opposite value
+    pub fn assign(&mut self, lit: Literal, reason: Option<usize>) -> bool
+    {
+        match self.assignments[lit.index]
+        {
+            Some(v) => v == lit.value,
+            None => {
+                self.assignments[lit.index] = Some(lit.value);
+                self.levels[lit.index] = self.decision_level();
+                self.reasons[lit.index] = reason;
+                self.trail.push(Literal{index: lit.index, value: lit.value, implicated: reason.is_some(), assigned: true});
+                true
+            }
+        }
+    }
+
+    ///Propagates every assignment on the trail that has not been propagated yet
+    /// # Returns
+    /// * The index of a falsified clause if a conflict was found
+    pub fn propagate(&mut self) -> Option<usize>
+    {
+        if self.falsified.is_some() { return self.falsified; }
+        while self.propagated < self.trail.len()
+        {
+            let false_code = negated_code(&self.trail[self.propagated]);
+            self.propagated += 1;
+            self.propagations += 1;
+            let mut watchers = std::mem::take(&mut self.watches[false_code]);
+            let mut kept = 0;
+            let mut conflict = None;
+            let mut i = 0;
+            while i < watchers.len()
+            {
+                let c = watchers[i];
+                i += 1;
+                //keep the false literal in the second position
+                if code(&self.clauses[c][0]) == false_code { self.clauses[c].swap(0, 1); }
+                let first = self.clauses[c][0];
+                if self.lit_value(&first) == Some(true)
+                {
+                    watchers[kept] = c;
+                    kept += 1;
+                    continue;
+                }
+                //look for a literal that is not false to watch instead
+                let replacement = (2..self.clauses[c].len()).find(|k| self.lit_value(&self.clauses[c][*k]) != Some(false));
+                if let Some(k) = replacement
+                {
+                    self.clauses[c].swap(1, k);
+                    let new_code = code(&self.clauses[c][1]);
+                    self.watches[new_code].push(c);
+                    continue;
+                }
+                watchers[kept] = c;
+                kept += 1;
+                if self.lit_value(&first) == Some(false)
+                {
+                    conflict = Some(c);
+                    break;
+                }
+                self.assign(first, Some(c));
+            }
+            //the clauses after a conflict were not visited and keep watching the literal
+            while i < watchers.len()
+            {
+                watchers[kept] = watchers[i];
+                kept += 1;
+                i += 1;
+            }
+            watchers.truncate(kept);
+            watchers.append(&mut self.watches[false_code]);
+            self.watches[false_code] = watchers;
+            if conflict.is_some()
+            {
+                self.propagated = self.trail.len();
+                return conflict;
+            }
+        }
+        None
+    }
+
+    ///Undoes every assignment made after the given decision level
+    pub fn backtrack(&mut self, level: usize)
+    {
+        if self.decision_level() <= level { return; }
+        let limit = self.trail_limits[level];
+        for lit in self.trail.drain(limit..)
+        {
+            self.saved_phases[lit.index] = self.assignments[lit.index];
+            self.assignments[lit.index] = None;
+            self.reasons[lit.index] = None;
+        }
+        self.trail_limits.truncate(level);
+        self.propagated = self.propagated.min(self.trail.len());
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{phi::Phi, clause::{Clause, Literal}};
+    use super::Propagator;
+
+    #[test]
+    fn propagate()
+    {
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c3(-1,2,3),
+            Clause::new_c2(-2,4),
+            Clause::new_c3(-3,-4,5),
+            Clause::new_c1(1)
+        ]);
+        let mut propagator = Propagator::new(&phi, phi.vars());
+        assert_eq!(propagator.propagate(), None);
+        assert_eq!(propagator.assignments(), &[Some(true), None, None, None, None]);
+
+        propagator.decide(Literal::from_isize(-3));
+        assert_eq!(propagator.propagate(), None);
+        assert_eq!(propagator.assignments(), &[Some(true), Some(true), Some(false), Some(true), None]);
+        assert_eq!(propagator.reason(1), Some(0));
+        assert_eq!(propagator.level(3), 1);
+
+        propagator.backtrack(0);
+        assert_eq!(propagator.assignments(), &[Some(true), None, None, None, None]);
+        assert_eq!((propagator.saved_phase(2), propagator.saved_phase(4)), (Some(false), None));
+        propagator.decide(Literal::from_isize(-5));
+        propagator.decide(Literal::from_isize(4));
+        assert_eq!(propagator.propagate(), None);
+        assert_eq!(propagator.value(2), Some(false));
+        let decisions: Vec<isize> = propagator.decisions().map(|l| l.as_isize()).collect();
+        assert_eq!(decisions, vec![-5, 4]);
+
+        //clauses over new variables
+        propagator.backtrack(0);
+        propagator.add_vars(7);
+        propagator.add_clause(vec![Literal::from_isize(-4), Literal::from_isize(7)]);
+        propagator.decide(Literal::from_isize(2));
+        assert_eq!(propagator.propagate(), None);
+        assert_eq!(propagator.value(6), Some(true));
+
+        //implied clauses watch their last false literals, deleted clauses no longer propagate
+        propagator.backtrack(0);
+        propagator.decide(Literal::from_isize(3));
+        propagator.decide(Literal::from_isize(-5));
+        assert_eq!(propagator.propagate(), None);
+        let implied = propagator.add_implied(vec![Literal::from_isize(-3), Literal::from_isize(6), Literal::from_isize(5)]);
+        assert_eq!(propagator.clause(implied)[..2].iter().map(|l| l.as_isize()).collect::<Vec<isize>>(), vec![6, 5]);
+        assert_eq!((propagator.value(5), propagator.reason(5)), (Some(true), Some(implied)));
+        assert!(propagator.is_reason(implied));
+        propagator.backtrack(1);
+        let clauses = propagator.num_clauses();
+        propagator.delete_clauses(&[implied]);
+        assert_eq!(propagator.num_clauses(), clauses - 1);
+        propagator.decide(Literal::from_isize(-5));
+        assert_eq!(propagator.propagate(), None);
+        assert_eq!(propagator.value(5), None);
+        assert_eq!(propagator.add_clause(vec![Literal::from_isize(6), Literal::from_isize(1)]), implied);
+    }
+
+    #[test]
+    fn conflict()
+    {
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c2(-1,2),
+            Clause::new_c2(-1,3),
+            Clause::new_c3(-2,-3,4),
+            Clause::new_c2(-4,-2)
+        ]);
+        let mut propagator = Propagator::new(&phi, phi.vars());
+        propagator.decide(Literal::from_isize(1));
+        assert!(propagator.propagate().is_some());
+        propagator.backtrack(0);
+        assert_eq!(propagator.trail().len(), 0);
+        propagator.decide(Literal::from_isize(-1));
+        assert_eq!(propagator.propagate(), None);
+
+        let phi = Phi::from_clauses(vec![Clause::new_c1(1), Clause::new_c1(-1)]);
+        let mut propagator = Propagator::new(&phi, phi.vars());
+        assert_eq!(propagator.propagate(), Some(1));
+    }
+}
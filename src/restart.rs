@@ -0,0 +1,345 @@
+use std::str::FromStr;
+use crate::{propagation::Propagator, error::Error};
+
+/*
+Restarts and phase selection of the searches.
+A restart undoes every decision, keeping the literals forced at level 0 and, for CDCL, the learned clauses,
+so that a long run is not stuck below bad early decisions. The policy decides after how many conflicts
+the search starts again: a fixed sequence (Luby, geometric) or the quality of the recent conflicts (glucose).
+The phase is the value tried first for the branching variable: the one proposed by the search, the value the
+variable had when it was last unassigned, or the value it had in the longest assignment found without conflicts.
+*/
+
+///When the search starts again from the first decision level
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RestartPolicy
+{
+    ///the search never restarts
+    #[default]
+    Never,
+    ///the Luby sequence 1, 1, 2, 1, 1, 2, 4, ... multiplied by the given number of conflicts
+    Luby(u64),
+    ///first conflicts before the first restart, multiplied by factor after each one
+    Geometric{first: u64, factor: f64},
+    ///when the literal block distance of the recent conflicts is well above the average of the whole search
+    Glucose
+}
+
+///Parses the name of a policy: never, luby, geometric or glucose, with the default parameters
+impl FromStr for RestartPolicy
+{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<RestartPolicy, Error>
+    {
+        match s
+        {
+            "never" => Ok(RestartPolicy::Never),
+            "luby" => Ok(RestartPolicy::Luby(LUBY_UNIT)),
+            "geometric" => Ok(RestartPolicy::Geometric{first: GEOMETRIC_FIRST, factor: GEOMETRIC_FACTOR}),
+            "glucose" => Ok(RestartPolicy::Glucose),
+            other => Err(Error::new(&format!("unknown restart policy \"{other}\"")))
+        }
+    }
+}
+
+impl RestartPolicy
+{
+    ///returns an error if the search would restart forever: a Luby unit or a first interval of 0 conflicts,
+    ///or a geometric factor that does not grow the interval
+    /// # Example
+    /// ```
+    /// use colombini_sat::restart::RestartPolicy;
+    ///
+    /// assert!(RestartPolicy::Luby(100).check().is_ok());
+    /// assert!(RestartPolicy::Geometric{first: 2, factor: 1.0}.check().is_err());
+    /// ```
+    pub fn check(&self) -> Result<(), Error>
+    {
+        match *self
+        {
+            RestartPolicy::Luby(0) => Err(Error::new("the Luby unit must be at least 1 conflict")),
+            RestartPolicy::Geometric{first: 0, ..} => Err(Error::new("the first geometric interval must be at least 1 conflict")),
+            RestartPolicy::Geometric{factor, ..} if factor.is_nan() || factor <= 1.0 => Err(Error::new(&format!("the geometric factor must be greater than 1, not {factor}"))),
+            _ => Ok(())
+        }
+    }
+}
+
+const LUBY_UNIT: u64 = 100;
+const GEOMETRIC_FIRST: u64 = 100;
+const GEOMETRIC_FACTOR: f64 = 1.5;
+//smoothing of the fast and of the slow moving averages of the glucose policy
+const FAST_ALPHA: f64 = 1.0/32.0;
+const SLOW_ALPHA: f64 = 1.0/4096.0;
+//the recent conflicts must be this much worse than the average to restart
+const GLUCOSE_MARGIN: f64 = 1.25;
+const GLUCOSE_MIN_CONFLICTS: u64 = 50;
+
+///returns the i-th element of the Luby sequence, from 1
+/// # Example
+/// ```
+/// use colombini_sat::restart::luby;
+///
+/// let sequence: Vec<u64> = (1..=8).map(luby).collect();
+/// assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1]);
+/// ```
+pub fn luby(mut i: u64) -> u64
+{
+    loop
+    {
+        //the smallest complete subsequence, of length 2^k - 1, that contains i
+        let mut k = 1;
+        while (1 << k) - 1 < i { k += 1; }
+        if (1 << k) - 1 == i { return 1 << (k - 1); }
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+
+///an exponential moving average, the plain average while there are fewer values than 1/alpha
+fn average(average: f64, value: f64, alpha: f64, n: u64) -> f64
+{
+    let alpha = alpha.max(1.0/n as f64);
+    average + alpha*(value - average)
+}
+
+///Counts the conflicts and tells the search when to restart
+/// # Example
+/// ```
+/// use colombini_sat::restart::{Restarts, RestartPolicy};
+///
+/// let mut restarts = Restarts::new(RestartPolicy::Luby(2), true);
+/// restarts.on_conflict(3);
+/// assert!(!restarts.should_restart());
+/// restarts.on_conflict(3);
+/// assert!(restarts.should_restart());
+/// restarts.restart();
+/// assert!(!restarts.should_restart());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Restarts
+{
+    policy: RestartPolicy,
+    //conflicts since the last restart and restarts done
+    conflicts: u64,
+    restarts: u64,
+    //conflicts since the beginning and the averages of their literal block distances
+    total: u64,
+    fast: f64,
+    slow: f64,
+    //searches that do not learn clauses lose their progress at every restart,
+    //so every policy waits twice as long after each one to stay complete
+    learning: bool
+}
+
+impl Restarts
+{
+    ///Creates the counters of a search
+    /// # Arguments
+    /// * `policy` - When to restart, accepted by RestartPolicy::check
+    /// * `learning` - true if the search keeps what it learned from the conflicts across the restarts
+    pub fn new(policy: RestartPolicy, learning: bool) -> Restarts
+    {
+        Restarts{policy, conflicts: 0, restarts: 0, total: 0, fast: 0.0, slow: 0.0, learning}
+    }
+
+    ///Records a conflict
+    /// # Arguments
+    /// * `lbd` - The number of decision levels in the clause learned from the conflict
+    pub fn on_conflict(&mut self, lbd: usize)
+    {
+        self.conflicts += 1;
+        self.total += 1;
+        self.fast = average(self.fast, lbd as f64, FAST_ALPHA, self.total);
+        self.slow = average(self.slow, lbd as f64, SLOW_ALPHA, self.total);
+    }
+
+    ///returns true if the search should undo every decision before the next one
+    pub fn should_restart(&self) -> bool
+    {
+        let growth: u64 = if self.learning { 1 } else { 1 << self.restarts.min(32) };
+        match self.policy
+        {
+            RestartPolicy::Never => false,
+            RestartPolicy::Luby(unit) => self.conflicts >= unit.saturating_mul(luby(self.restarts + 1)).saturating_mul(growth),
+            RestartPolicy::Geometric{first, factor} => {
+                self.conflicts as f64 >= first as f64*factor.powi(self.restarts.min(i32::MAX as u64) as i32)*growth as f64
+            },
+            RestartPolicy::Glucose => self.conflicts >= GLUCOSE_MIN_CONFLICTS*growth && self.fast > GLUCOSE_MARGIN*self.slow
+        }
+    }
+
+    ///Records a restart, the conflicts are counted again from 0
+    pub fn restart(&mut self)
+    {
+        self.conflicts = 0;
+        self.restarts += 1;
+    }
+
+    pub fn restarts(&self) -> u64
+    {
+        self.restarts
+    }
+}
+
+///The value tried first for the branching variable
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PhasePolicy
+{
+    ///the value proposed by the search: true for dpll and cdcl, the one of the heuristic for the lookahead
+    #[default]
+    Fixed,
+    ///the value the variable had when it was last unassigned
+    Saved,
+    ///the value the variable had in the longest assignment reached without conflicts, else the saved one
+    Target
+}
+
+///Parses the name of a policy: fixed, saved or target
+impl FromStr for PhasePolicy
+{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<PhasePolicy, Error>
+    {
+        match s
+        {
+            "fixed" => Ok(PhasePolicy::Fixed),
+            "saved" => Ok(PhasePolicy::Saved),
+            "target" => Ok(PhasePolicy::Target),
+            other => Err(Error::new(&format!("unknown phase policy \"{other}\"")))
+        }
+    }
+}
+
+///Chooses the value of the branching variables
+#[derive(Clone, Debug)]
+pub struct Phases
+{
+    policy: PhasePolicy,
+    target: Vec<Option<bool>>,
+    //the length of the trail the target was taken from
+    best: usize
+}
+
+impl Phases
+{
+    pub fn new(policy: PhasePolicy) -> Phases
+    {
+        Phases{policy, target: Vec::new(), best: 0}
+    }
+
+    ///returns the value to try first for the variable
+    /// # Arguments
+    /// * `propagator` - The assignments of the search, with the saved phases
+    /// * `index` - The branching variable, from 0
+    /// * `proposed` - The value the search would try without phase selection
+    pub fn value(&self, propagator: &Propagator, index: usize, proposed: bool) -> bool
+    {
+        match self.policy
+        {
+            PhasePolicy::Fixed => proposed,
+            PhasePolicy::Saved => propagator.saved_phase(index).unwrap_or(proposed),
+            PhasePolicy::Target => self.target.get(index).copied().flatten().or(propagator.saved_phase(index)).unwrap_or(proposed)
+        }
+    }
+
+    ///Records the assignments of a search that found no conflict, to be called before every decision
+    pub fn update(&mut self, propagator: &Propagator)
+    {
+        if self.policy == PhasePolicy::Target && propagator.trail().len() > self.best
+        {
+            self.best = propagator.trail().len();
+            self.target = propagator.assignments().to_vec();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{phi::Phi, clause::{Clause, Literal}, propagation::Propagator};
+    use super::{luby, Restarts, RestartPolicy, Phases, PhasePolicy};
+
+    ///returns the number of conflicts before each of the first restarts
+    fn intervals(policy: RestartPolicy, learning: bool, lbd: impl Fn(u64) -> usize) -> Vec<u64>
+    {
+        let mut restarts = Restarts::new(policy, learning);
+        let (mut intervals, mut conflicts, mut total) = (Vec::new(), 0, 0);
+        while intervals.len() < 5 && total < 10_000
+        {
+            restarts.on_conflict(lbd(total));
+            conflicts += 1;
+            total += 1;
+            if restarts.should_restart()
+            {
+                intervals.push(conflicts);
+                conflicts = 0;
+                restarts.restart();
+            }
+        }
+        intervals
+    }
+
+    #[test]
+    fn restarts()
+    {
+        let sequence: Vec<u64> = (1..=15).map(luby).collect();
+        assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+        assert_eq!(intervals(RestartPolicy::Luby(10), true, |_| 3), vec![10, 10, 20, 10, 10]);
+        assert_eq!(intervals(RestartPolicy::Geometric{first: 10, factor: 2.0}, true, |_| 3), vec![10, 20, 40, 80, 160]);
+        assert!(intervals(RestartPolicy::Never, true, |_| 3).is_empty());
+        //without learning the intervals also double after each restart
+        assert_eq!(intervals(RestartPolicy::Luby(10), false, |_| 3), vec![10, 20, 80, 80, 160]);
+        assert_eq!(intervals(RestartPolicy::Geometric{first: 10, factor: 1.5}, false, |_| 3), vec![10, 30, 90, 270, 810]);
+
+        //glucose restarts when the recent conflicts get worse than the average, never when they are steady
+        assert!(intervals(RestartPolicy::Glucose, true, |_| 3).is_empty());
+        let worse = intervals(RestartPolicy::Glucose, true, |total| if total < 500 { 2 } else { 10 });
+        assert_eq!(worse.len(), 5);
+        assert!(worse.iter().all(|conflicts| *conflicts >= 50));
+
+        //without learning the glucose policy waits longer after each restart
+        let mut restarts = Restarts::new(RestartPolicy::Glucose, false);
+        restarts.restart();
+        for conflict in 0..99 { restarts.on_conflict(if conflict < 60 { 1 } else { 20 }); }
+        assert!(!restarts.should_restart());
+        restarts.on_conflict(20);
+        assert!(restarts.should_restart());
+
+        assert_eq!("luby".parse::<RestartPolicy>().unwrap(), RestartPolicy::Luby(100));
+        assert!("often".parse::<RestartPolicy>().is_err());
+        for policy in [RestartPolicy::Luby(0), RestartPolicy::Geometric{first: 0, factor: 2.0}, RestartPolicy::Geometric{first: 2, factor: 1.0},
+            RestartPolicy::Geometric{first: 2, factor: 0.5}, RestartPolicy::Geometric{first: 2, factor: f64::NAN}]
+        {
+            assert!(policy.check().is_err(), "{policy:?} accepted");
+        }
+        assert_eq!("target".parse::<PhasePolicy>().unwrap(), PhasePolicy::Target);
+    }
+
+    #[test]
+    fn phases()
+    {
+        let phi = Phi::from_clauses(vec![Clause::new_c2(-1,2), Clause::new_c2(-2,-3)]);
+        let mut propagator = Propagator::new(&phi, 4);
+        let (fixed, saved, mut target) = (Phases::new(PhasePolicy::Fixed), Phases::new(PhasePolicy::Saved), Phases::new(PhasePolicy::Target));
+        assert!(saved.value(&propagator, 1, true));
+
+        propagator.decide(Literal::from_isize(1));
+        assert_eq!(propagator.propagate(), None);
+        target.update(&propagator);
+        propagator.backtrack(0);
+        propagator.decide(Literal::from_isize(-2));
+        target.update(&propagator);
+        propagator.backtrack(0);
+
+        assert!(fixed.value(&propagator, 2, true));
+        //x3 was false when the search backtracked, x2 was last unassigned while false
+        assert!(!saved.value(&propagator, 2, true));
+        assert!(!saved.value(&propagator, 1, true));
+        //the longest assignment had x2 true
+        assert!(target.value(&propagator, 1, false));
+        assert!(!target.value(&propagator, 2, true));
+        assert!(target.value(&propagator, 3, true));
+    }
+}
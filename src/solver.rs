@@ -1,10 +1,13 @@
-use std::collections::HashMap;
 use std::vec;
-use std::error;
+use std::io::BufRead;
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
+use log::{debug, trace};
 use petgraph::{graph::DiGraph,stable_graph::NodeIndex,Direction::Incoming};
 
-use crate::{phi::Phi, error::Error, clause::{Clause, Literal, Implication}, two_satisfiability::solve_2_sat};
+use crate::{phi::Phi, error::Error, clause::{Clause, Literal}, propagation::Propagator, two_satisfiability::{fixed_literals, contradiction}, stats::SolveStats, dimacs::ParseMode, drat::Proof, heuristics::{BranchingHeuristic, Heuristic}, restart::{Restarts, RestartPolicy, Phases, PhasePolicy}, learned::{self, LearnedClauses, ReductionPolicy}, implications::BinaryImplications};
 
 /*
 Core idea: at each step identify the i-th literal that is forced to be true or false within their i-th phi_prime
@@ -13,10 +16,70 @@ phi_prime_i being a subset of phi, composed by the clauses that contains the lit
 
 pub struct Solver{
     pub phi: Phi,
-    pub solution: Option<Vec<bool>>
+    pub solution: Option<Vec<bool>>,
+    //variables of the input formula, the ones after them were introduced by the 3-SAT conversion
+    original_vars: usize,
+    parse_time: Duration,
+    //the branching heuristic of the lookahead and the seed that breaks its ties
+    heuristic: Heuristic,
+    seed: u64,
+    //when the searches start again, the value they try first and which learned clauses they delete
+    policies: Policies,
+    //evaluate the formula on every model before returning it
+    verify: bool,
+    //0-based indices of the variables introduced by the 3-SAT conversion, the variables added later come after them
+    aux: Range<usize>,
+    //the engine of the incremental searches, created by the first one and kept with its learned clauses
+    incremental: Option<Cdcl>,
+    failed: Vec<isize>,
+    //polled by the incremental searches, they stop when it returns true
+    terminate: Option<Box<dyn FnMut() -> bool>>
+}
+
+impl Default for Solver{
+    fn default() -> Self{
+        Solver::new()
+    }
+}
+
+///Parses a dimacs formula
+/// # Example
+/// ```
+/// use colombini_sat::solver::Solver;
+/// 
+/// let solver: Solver = "p cnf 2 2\n1 2 0\n-1 0\n".parse().unwrap();
+/// assert_eq!(solver.solve().unwrap(), Some(vec![-1, 2]));
+/// ```
+impl FromStr for Solver{
+    type Err = Error;
+
+    fn from_str(s: &str)->Result<Solver,Error>{
+        Solver::from_reader(s.as_bytes())
+    }
 }
 
 impl Solver{
+    ///Creates a Solver with no clauses, to be filled with add_clause
+    /// # Example
+    /// ```
+    /// use colombini_sat::solver::Solver;
+    /// 
+    /// let mut solver = Solver::new();
+    /// solver.add_clause(&[1, 2]);
+    /// solver.add_clause(&[-1]);
+    /// assert_eq!(solver.solve_with_assumptions(&[]).unwrap(), Some(vec![-1, 2]));
+    /// ```
+    pub fn new()->Solver{
+        let mut solver = Solver::with_phi(Phi::new(), 0, Duration::ZERO);
+        solver.aux = 0..0;
+        solver
+    }
+
+    fn with_phi(phi: Phi, original_vars: usize, parse_time: Duration)->Solver{
+        let aux = original_vars..phi.vars().max(original_vars);
+        Solver{phi, solution: None, original_vars, parse_time, heuristic: Heuristic::default(), seed: 0, policies: Policies::default(), verify: false, aux, incremental: None, failed: Vec::new(), terminate: None}
+    }
+
     ///Creates a Solver object from a path to a dmacs file\
         /// # Arguments
         /// * `dmacs_path` - A string slice that holds the path to the dmacs file
@@ -28,14 +91,174 @@ impl Solver{
         /// 
         /// let solver = Solver::create("TestData/test.cnf").unwrap();
         /// ```
-    pub fn create(dmacs_path: &str)->Result<Solver,Box<dyn error::Error>>{
+    pub fn create(dmacs_path: &str)->Result<Solver,Error>{
         
+        let start = Instant::now();
         match Phi::from_file(dmacs_path){
-            Ok(phi) => Ok(Solver{phi: phi, solution: None}),
+            Ok(phi) => {
+                let original_vars = phi.vars();
+                Ok(Solver::with_phi(phi, original_vars, start.elapsed()))
+            },
             Err(e) => Err(e)
         }
     }
 
+    ///Creates a Solver object from a path to a dmacs file, in lenient mode the deviations from the format are
+    ///returned as warnings. The assignments have a value for every variable declared in the header, used or not.
+        /// # Example
+        /// ```
+        /// use colombini_sat::{solver::Solver, dimacs::ParseMode};
+        /// 
+        /// let (solver, warnings) = Solver::create_with_mode("TestData/mismatch.cnf", ParseMode::Lenient).unwrap();
+        /// assert_eq!(warnings.len(), 1);
+        /// assert_eq!(solver.solve().unwrap().unwrap().len(), 4);
+        /// ```
+    pub fn create_with_mode(dmacs_path: &str, mode: ParseMode)->Result<(Solver,Vec<Error>),Error>{
+        let start = Instant::now();
+        let (phi, warnings) = Phi::from_file_with_mode(dmacs_path, mode)?;
+        let original_vars = phi.vars();
+        Ok((Solver::with_phi(phi, original_vars, start.elapsed()), warnings))
+    }
+
+    ///Creates a Solver object reading a dmacs formula from any reader, e.g. stdin or a byte slice
+        /// # Example
+        /// ```
+        /// use colombini_sat::solver::Solver;
+        /// 
+        /// let solver = Solver::from_reader("p cnf 3 2\n1 -2 3 0\n-1 0\n".as_bytes()).unwrap();
+        /// assert!(solver.solve().unwrap().is_some());
+        /// ```
+    pub fn from_reader<R: BufRead>(reader: R)->Result<Solver,Error>{
+        let start = Instant::now();
+        let phi = Phi::from_reader(reader)?;
+        let original_vars = phi.vars();
+        Ok(Solver::with_phi(phi, original_vars, start.elapsed()))
+    }
+
+    ///Same as from_reader, in lenient mode the deviations from the format are returned as warnings
+    pub fn from_reader_with_mode<R: BufRead>(reader: R, mode: ParseMode)->Result<(Solver,Vec<Error>),Error>{
+        let start = Instant::now();
+        let (phi, warnings) = Phi::from_reader_with_mode(reader, mode)?;
+        let original_vars = phi.vars();
+        Ok((Solver::with_phi(phi, original_vars, start.elapsed()), warnings))
+    }
+
+    ///Creates a Solver object for a formula built in memory, e.g. by a Builder
+    /// # Example
+    /// ```
+    /// use colombini_sat::{solver::Solver, phi::Phi, clause::Clause};
+    /// 
+    /// let solver = Solver::from_phi(Phi::from_clauses(vec![Clause::new_c2(1,2), Clause::new_c1(-1)]));
+    /// assert_eq!(solver.solve().unwrap(), Some(vec![-1, 2]));
+    /// ```
+    pub fn from_phi(phi: Phi)->Solver{
+        let original_vars = phi.vars();
+        Solver::with_phi(phi, original_vars, Duration::ZERO)
+    }
+
+    ///Creates a Solver object from a path to a dmacs file of any k-SAT formula,
+    ///clauses wider than three literals are split in 3-SAT clauses so that the lookahead algorithm can be used.\
+    ///The assignments returned by the solver only contain the variables of the original formula.
+        /// # Arguments
+        /// * `dmacs_path` - A string slice that holds the path to the dmacs file
+        /// # Example
+        /// ```
+        /// use colombini_sat::solver::Solver;
+        /// 
+        /// let solver = Solver::create_3sat("TestData/wide.cnf").unwrap();
+        /// assert_eq!(solver.solve().unwrap().unwrap().len(), 6);
+        /// ```
+    pub fn create_3sat(dmacs_path: &str)->Result<Solver,Error>{
+        let start = Instant::now();
+        let (phi, original_vars) = Phi::from_file_3sat(dmacs_path)?;
+        Ok(Solver::with_phi(phi, original_vars, start.elapsed()))
+    }
+
+    ///Sets the heuristic choosing the branching variable of the lookahead, by default the lookahead score
+    pub fn set_heuristic(&mut self, heuristic: Heuristic){
+        self.heuristic = heuristic;
+    }
+
+    ///Sets the seed breaking the ties of the heuristic, runs with the same seed make the same choices
+    /// # Example
+    /// ```
+    /// use colombini_sat::{solver::Solver, heuristics::Heuristic};
+    /// 
+    /// let mut solver = Solver::create("TestData/uf50-01.cnf").unwrap();
+    /// solver.set_heuristic(Heuristic::Moms);
+    /// solver.set_seed(7);
+    /// assert_eq!(solver.solve_with_stats().unwrap().1.decisions, solver.solve_with_stats().unwrap().1.decisions);
+    /// ```
+    pub fn set_seed(&mut self, seed: u64){
+        self.seed = seed;
+    }
+
+    ///Sets when every search, lookahead, dpll and cdcl, undoes its decisions and starts again, by default never.\
+    ///The incremental engine takes the policies set before the first call to solve_with_assumptions.
+    /// # Returns
+    /// * An error, keeping the previous policy, if RestartPolicy::check rejects the policy
+    /// # Example
+    /// ```
+    /// use colombini_sat::{solver::Solver, restart::{RestartPolicy, PhasePolicy}};
+    /// 
+    /// let mut solver = Solver::create("TestData/uf50-01.cnf").unwrap();
+    /// solver.set_restarts(RestartPolicy::Luby(4)).unwrap();
+    /// solver.set_phase(PhasePolicy::Saved);
+    /// let (solution, stats) = solver.solve_dpll_with_stats().unwrap();
+    /// assert!(solution.is_some() && stats.restarts > 0);
+    /// assert!(solver.set_restarts(RestartPolicy::Luby(0)).is_err());
+    /// ```
+    pub fn set_restarts(&mut self, restarts: RestartPolicy)->Result<(),Error>{
+        restarts.check()?;
+        self.policies.restarts = restarts;
+        Ok(())
+    }
+
+    ///Sets how the searches choose the value tried first for the branching variable, by default the one they propose
+    pub fn set_phase(&mut self, phase: PhasePolicy){
+        self.policies.phase = phase;
+    }
+
+    ///Sets when the clauses learned by cdcl and the literals forced by the lookahead are deleted,
+    ///by default half of them beyond 2000 clauses, keeping those with the fewest decision levels
+    /// # Example
+    /// ```
+    /// use colombini_sat::{solver::Solver, learned::ReductionPolicy};
+    /// 
+    /// let mut solver = Solver::create("TestData/unsat30.cnf").unwrap();
+    /// solver.set_reduction(ReductionPolicy::Activity{limit: 10, increment: 5});
+    /// let (solution, stats) = solver.solve_cdcl_with_stats().unwrap();
+    /// assert!(solution.is_none() && stats.deleted_clauses > 0);
+    /// ```
+    pub fn set_reduction(&mut self, reduction: ReductionPolicy){
+        self.policies.reduction = reduction;
+    }
+
+    ///Enables the verification of the models: the formula is evaluated on every assignment found by a search,
+    ///the variables left unassigned included, before it is returned.
+    ///A model that falsifies a clause is a bug of the search, the searches return it as Error::ModelFalsified.
+    /// # Example
+    /// ```
+    /// use colombini_sat::solver::Solver;
+    /// 
+    /// let mut solver = Solver::create("TestData/uf50-01.cnf").unwrap();
+    /// solver.set_verify(true);
+    /// assert!(solver.solve().unwrap().is_some());
+    /// ```
+    pub fn set_verify(&mut self, verify: bool){
+        self.verify = verify;
+    }
+
+    ///Evaluates the formula on a full assignment, the variables introduced by the 3-SAT conversion included
+    /// # Returns
+    /// * Error::ModelFalsified with the first clause falsified
+    pub fn check_model(&self, assignment: &[bool])->Result<(),Error>{
+        match self.phi.falsified(assignment){
+            Some(index) => Err(Error::ModelFalsified{index, clause: self.phi.clauses()[index].clone()}),
+            None => Ok(())
+        }
+    }
+
     ///Returns the number of literals in the formula
     pub fn num_variables(&self)->usize{
         self.phi.vars()
@@ -43,117 +266,557 @@ impl Solver{
 
     ///Returns the number of clauses in the formula
     pub fn num_clauses(&self)->usize{
-        self.phi.clauses.len()
+        self.phi.clauses().len()
     }
 
 
-    ///Returns a satisfying assignment for the formula if it exists, None otherwise
+    ///Returns a satisfying assignment for the formula if it exists, None otherwise.\
+    ///The lookahead algorithm only handles 3-SAT formulas, wider formulas are solved with CDCL.
+    /// # Returns
+    /// * Error::ModelFalsified if the verification is enabled and the model found falsifies a clause
     /// # Example
     /// ```
     /// use colombini_sat::solver::Solver;
     /// 
     /// let solver = Solver::create("TestData/test.cnf").unwrap();
-    /// let solution = solver.solve();
+    /// let solution = solver.solve().unwrap();
     /// match solution{
     ///    Some(solution) => {println!("SAT({:?})",solution);},
     ///   None => {println!("UNSAT");}
     /// }
     /// ```
-    pub fn solve(&self)->Option<Vec<isize>>{
-        let solution = solve(&self.phi);
-        match solution{
-            Some(solution) => {
-                let mut assignment: Vec<isize> = vec![0;self.phi.vars()];
-                for (index,value) in solution.iter().enumerate(){
-                    if *value{
-                        assignment[index] = index as isize +1;
-                    }
-                    else{
-                        assignment[index] = -(index as isize +1);
-                    }
-                }
-                return Some(assignment);
-            },
-            None => None
+    pub fn solve(&self)->Result<Option<Vec<isize>>,Error>{
+        Ok(self.solve_with_stats()?.0)
+    }
+
+    ///Same as solve, also returns the statistics collected during the search
+    /// # Example
+    /// ```
+    /// use colombini_sat::solver::Solver;
+    /// 
+    /// let solver = Solver::create("TestData/test.cnf").unwrap();
+    /// let (solution, stats) = solver.solve_with_stats().unwrap();
+    /// assert!(solution.is_some());
+    /// println!("{stats}");
+    /// ```
+    pub fn solve_with_stats(&self)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        self.solve_lookahead(None)
+    }
+
+    ///Same as solve_with_stats, also logs to the proof every clause derived by the search,
+    ///so that an unsatisfiable answer can be verified by a DRAT checker.\
+    ///The proof refers to the formula in phi, which for create_3sat is the converted one.
+    /// # Example
+    /// ```
+    /// use colombini_sat::{solver::Solver, drat::{Proof, ProofFormat}};
+    /// 
+    /// let solver = Solver::create("TestData/unsat30.cnf").unwrap();
+    /// let mut proof = Proof::new(std::io::sink(), ProofFormat::Text);
+    /// let (solution, _) = solver.solve_with_proof(&mut proof).unwrap();
+    /// assert!(solution.is_none());
+    /// assert!(proof.additions() > 0);
+    /// proof.finish().unwrap();
+    /// ```
+    pub fn solve_with_proof(&self, proof: &mut Proof)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        self.solve_lookahead(Some(proof))
+    }
+
+    fn solve_lookahead(&self, proof: Option<&mut Proof>)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        self.solve_lookahead_with(proof, &mut *self.heuristic.create(self.seed))
+    }
+
+    ///Same as solve_with_stats, branching on the variables chosen by any heuristic, also one defined by the application.\
+    ///Formulas that are not 3-SAT are solved with CDCL, which keeps its own VSIDS scores.
+    /// # Example
+    /// ```
+    /// use colombini_sat::{solver::Solver, heuristics::JeroslowWang};
+    /// 
+    /// let solver = Solver::create("TestData/uf50-01.cnf").unwrap();
+    /// let (solution, stats) = solver.solve_with_heuristic(&mut JeroslowWang::new(42)).unwrap();
+    /// assert!(solution.is_some());
+    /// ```
+    pub fn solve_with_heuristic(&self, heuristic: &mut dyn BranchingHeuristic)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        self.solve_lookahead_with(None, heuristic)
+    }
+
+    fn solve_lookahead_with(&self, proof: Option<&mut Proof>, heuristic: &mut dyn BranchingHeuristic)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        if !self.phi.is_3_sat(){
+            return self.solve_cdcl_search(proof);
+        }
+        let mut stats = SolveStats::new();
+        stats.parse_time = self.parse_time;
+        let solution = lookahead_search(&self.phi, &mut stats, proof, heuristic, self.policies);
+        Ok((solution.map(|solution| self.to_model(&solution)).transpose()?, stats))
+    }
+
+    ///Returns a satisfying assignment for the formula if it exists, None otherwise,
+    ///searching with conflict driven clause learning and non-chronological backjumping
+    /// # Example
+    /// ```
+    /// use colombini_sat::solver::Solver;
+    /// 
+    /// let solver = Solver::create("TestData/test.cnf").unwrap();
+    /// let solution = solver.solve_cdcl().unwrap();
+    /// assert!(solution.is_some());
+    /// ```
+    pub fn solve_cdcl(&self)->Result<Option<Vec<isize>>,Error>{
+        Ok(self.solve_cdcl_with_stats()?.0)
+    }
+
+    ///Same as solve_cdcl, also returns the statistics collected during the search
+    pub fn solve_cdcl_with_stats(&self)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        self.solve_cdcl_search(None)
+    }
+
+    ///Same as solve_cdcl_with_stats, also logs the learned clauses to the proof
+    pub fn solve_cdcl_with_proof(&self, proof: &mut Proof)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        self.solve_cdcl_search(Some(proof))
+    }
+
+    fn solve_cdcl_search(&self, proof: Option<&mut Proof>)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        let mut stats = SolveStats::new();
+        stats.parse_time = self.parse_time;
+        let solution = cdcl_search(&self.phi, &mut stats, proof, self.policies).map(|solution| self.to_literals(&solution));
+        Ok((solution.transpose()?, stats))
+    }
+
+    ///Returns a satisfying assignment for the formula if it exists, None otherwise,
+    ///searching with plain DPLL: unit propagation and chronological backtracking
+    /// # Example
+    /// ```
+    /// use colombini_sat::solver::Solver;
+    /// 
+    /// let solver = Solver::create("TestData/test.cnf").unwrap();
+    /// assert!(solver.solve_dpll().unwrap().is_some());
+    /// ```
+    pub fn solve_dpll(&self)->Result<Option<Vec<isize>>,Error>{
+        Ok(self.solve_dpll_with_stats()?.0)
+    }
+
+    ///Same as solve_dpll, also returns the statistics collected during the search
+    pub fn solve_dpll_with_stats(&self)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        self.solve_dpll_search(None)
+    }
+
+    ///Same as solve_dpll_with_stats, also logs to the proof the negation of every failed branch
+    pub fn solve_dpll_with_proof(&self, proof: &mut Proof)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        self.solve_dpll_search(Some(proof))
+    }
+
+    fn solve_dpll_search(&self, proof: Option<&mut Proof>)->Result<(Option<Vec<isize>>, SolveStats),Error>{
+        let mut stats = SolveStats::new();
+        stats.parse_time = self.parse_time;
+        let solution = dpll_search(&self.phi, &mut stats, proof, self.policies).map(|solution| self.to_literals(&solution));
+        Ok((solution.transpose()?, stats))
+    }
+
+    ///Adds a clause to the formula, the next searches take it into account.\
+    ///Literals are signed 1-based variables as in dimacs, variables never seen before are added to the formula.
+    ///The model and the failed assumptions of the last search are discarded.
+    /// # Panics
+    /// * If a literal is 0
+    pub fn add_clause(&mut self, clause: &[isize]){
+        let literals: Vec<isize> = clause.iter().map(|l| self.to_internal(*l)).collect();
+        let clause = Clause::new_cn(&literals);
+        if !clause.is_empty(){
+            self.original_vars = self.original_vars.max(self.to_external(clause.max_literal() as isize + 1) as usize);
         }
+        if let Some(cdcl) = &mut self.incremental{
+            cdcl.add_clause(&clause.literals);
+        }
+        self.phi.add_clause(clause);
+        self.solution = None;
+        self.failed.clear();
+    }
+
+    ///Searches with CDCL under the assumptions, literals taken as true for this search only.\
+    ///The clauses learned are kept for the next searches, so a formula can be solved many times
+    ///while it grows, as in bounded model checking. The model is also stored for value,
+    ///when there is none the assumptions that made the search fail are available to failed.
+    /// # Example
+    /// ```
+    /// use colombini_sat::solver::Solver;
+    /// 
+    /// let mut solver = Solver::new();
+    /// solver.add_clause(&[-1, 2]);
+    /// solver.add_clause(&[-2, 3]);
+    /// assert!(solver.solve_with_assumptions(&[1, -3, 4]).unwrap().is_none());
+    /// assert!(solver.failed(1) && solver.failed(-3) && !solver.failed(4));
+    /// assert!(solver.solve_with_assumptions(&[1]).unwrap().is_some());
+    /// assert_eq!(solver.value(3), Some(true));
+    /// ```
+    pub fn solve_with_assumptions(&mut self, assumptions: &[isize])->Result<Option<Vec<isize>>,Error>{
+        let assumptions: Vec<Literal> = assumptions.iter().map(|l| Literal::from_isize(self.to_internal(*l))).collect();
+        let mut stats = SolveStats::new();
+        let cdcl = self.incremental.get_or_insert_with(|| Cdcl::new(&self.phi, self.policies));
+        let solution = cdcl.solve(&assumptions, &mut stats, &mut self.terminate);
+        let failed: Vec<isize> = cdcl.failed.iter().map(|l| l.as_isize()).collect();
+        debug!("incremental search: {} decisions, {} conflicts", stats.decisions, stats.backtracks);
+        self.failed = failed.into_iter().map(|l| self.to_external(l)).collect();
+        self.solution = None;
+        let model = solution?.map(|solution| self.to_literals(&solution)).transpose()?;
+        self.solution = model.as_ref().map(|model| model.iter().map(|l| *l > 0).collect());
+        Ok(model)
+    }
+
+    ///Sets a callback polled by solve_with_assumptions on every conflict, the search stops with Error::Terminated
+    ///as soon as it returns true. The other searches always run to the end.
+    /// # Example
+    /// ```
+    /// use colombini_sat::{solver::Solver, error::Error};
+    /// 
+    /// let mut solver = Solver::create("TestData/unsat30.cnf").unwrap();
+    /// solver.set_terminate(Some(Box::new(|| true)));
+    /// assert!(matches!(solver.solve_with_assumptions(&[]), Err(Error::Terminated)));
+    /// solver.set_terminate(None);
+    /// assert!(solver.solve_with_assumptions(&[]).unwrap().is_none());
+    /// ```
+    pub fn set_terminate(&mut self, terminate: Option<Box<dyn FnMut() -> bool>>){
+        self.terminate = terminate;
+    }
+
+    ///returns true if the assumption is one of those that made the last search fail
+    pub fn failed(&self, assumption: isize)->bool{
+        self.failed.contains(&assumption)
+    }
+
+    ///returns the value of the literal in the model of the last search, None if there is no model
+    ///or the variable is not in the formula
+    pub fn value(&self, lit: isize)->Option<bool>{
+        let value = self.solution.as_ref()?.get(lit.unsigned_abs().checked_sub(1)?)?;
+        Some(*value == (lit > 0))
+    }
+
+    ///maps a variable of the user to the formula, skipping the variables of the 3-SAT conversion
+    fn to_internal(&self, lit: isize)->isize{
+        if lit.unsigned_abs() <= self.aux.start {lit} else {lit + lit.signum()*self.aux.len() as isize}
+    }
+
+    fn to_external(&self, lit: isize)->isize{
+        if lit.unsigned_abs() <= self.aux.start {lit} else {lit - lit.signum()*self.aux.len() as isize}
+    }
+
+    ///converts an assignment to signed literals, the variables left unassigned are false
+    fn to_literals(&self, solution: &[Option<bool>])->Result<Vec<isize>,Error>{
+        let solution: Vec<bool> = solution.iter().map(|value| value.unwrap_or(false)).collect();
+        self.to_model(&solution)
+    }
+
+    ///converts a full assignment to signed literals, dropping the variables introduced by the 3-SAT conversion.\
+    ///With verification enabled a falsified clause is returned as Error::ModelFalsified.
+    fn to_model(&self, solution: &[bool])->Result<Vec<isize>,Error>{
+        if self.verify{
+            self.check_model(solution)?;
+        }
+        Ok(solution.iter().enumerate().filter(|(index,_)| !self.aux.contains(index)).take(self.original_vars).enumerate().map(|(index,(_,value))| {
+            if *value { index as isize + 1 } else { -(index as isize + 1) }
+        }).collect())
     }
 }
 
-/* 
-fn conflict_to_clauses(conflict: &Vec<Literal>)->Vec<Clause>{
-    //TODO
+///propagates keeping track of the time spent and of the conflicts found
+fn timed_propagate(propagator: &mut Propagator, stats: &mut SolveStats) -> Option<usize>
+{
+    let start = Instant::now();
+    let conflict = propagator.propagate();
+    stats.propagation_time += start.elapsed();
+    if conflict.is_some() { stats.conflicts += 1; }
+    conflict
 }
-*/
-fn _dpll(phi: &Phi, mut assignments: Vec<Option<bool>>) -> Option<Vec<Option<bool>>>
+
+///Logs a clause derived under the current decisions: the given literals or the negation of one of the decisions.\
+///With no literals it is the clause learned when the current branch fails.
+fn derive(proof: &mut Option<&mut Proof>, propagator: &Propagator, literals: &[Literal])
 {
-    let new_phi = phi.autoreduce_with_assignments(&mut assignments);
-    
-    if new_phi.clauses.len() == 0
+    if let Some(proof) = proof
     {
-        return Some(assignments);
+        let mut clause = literals.to_vec();
+        clause.extend(propagator.decisions().map(|l| l.negated()));
+        proof.add(&clause);
     }
-    else if new_phi.clauses[0] == Clause::Empty
+}
+
+///Logs a literal forced because the 2-SAT formula of the opposite branch is not satisfiable.\
+///Unit propagation may not see the contradiction, so the literal that implies its negation is refuted first.
+fn derive_refuted(proof: &mut Option<&mut Proof>, propagator: &Propagator, branch: &[(Literal, Literal)], lit: Literal)
+{
+    if proof.is_none() { return; }
+    let branch = Phi::from_clauses(branch.iter().map(|(a, b)| Clause::new(if a == b { vec![*a] } else { vec![*a, *b] })).collect());
+    if let Some(witness) = contradiction(&branch)
     {
-        return None;
+        derive(proof, propagator, &[lit, Literal::from_isize(-witness)]);
     }
-    else 
+    derive(proof, propagator, &[lit]);
+}
+
+///Collects the 2-SAT formula of the branch where lit is true: the clauses of the formula with its variable that the
+///branch does not satisfy, without their false literals, and a unit clause for every literal known to follow from lit.
+///A unit clause is a pair of the same literal.
+/// # Returns
+/// * The literals true in every model of the branch, or an error if it is refuted or a clause has more than two
+///   unassigned literals, the clauses are then cleared
+fn probe(original: &Phi, propagator: &Propagator, implications: &mut BinaryImplications, lit: Literal, clauses: &mut Vec<(Literal, Literal)>) -> Result<Vec<Literal>, Error>
+{
+    clauses.clear();
+    let value = |l: &Literal| if l.index == lit.index { Some(l.value == lit.value) } else { propagator.value(l.index).map(|v| v == l.value) };
+    let implied = implications.implied(lit);
+    let occurrences = original.var_occurrences(lit.index);
+    let reduced = occurrences.iter().map(|c| original.clauses()[*c].literals.as_slice())
+        .chain(implied.iter().map(std::slice::from_ref));
+    for literals in reduced
     {
-        //find a literal that is not assigned
-        let literals = new_phi.get_variables();
-        let mut found = false;
-        let mut literal = 0;
-        for l in literals
+        if literals.iter().any(|l| value(l) == Some(true)) { continue; }
+        let mut free: [Option<Literal>; 2] = [None, None];
+        let mut width = 0;
+        for l in literals.iter().filter(|l| value(l).is_none())
         {
-            if assignments[l] == None
-            {
-                found = true;
-                literal = l;
-                break;
-            }
+            if free.iter().flatten().any(|f| f.index == l.index && f.value == l.value) { continue; }
+            if width < 2 { free[width] = Some(*l); }
+            width += 1;
         }
-        if !found
+        let error = match free
         {
-            let final_phi = new_phi.reduce(&assignments);
-            if final_phi.clauses.len() == 0
-            {
-                return Some(assignments);
+            [Some(a), Some(b)] if width == 2 => { clauses.push((a, b)); continue; },
+            [Some(a), None] => { clauses.push((a, a)); continue; },
+            [None, _] => Error::Unsatisfiable{reason: "found empty clause in 2-sat"},
+            _ => Error::ClauseTooWide{at: None, width, max: 2}
+        };
+        clauses.clear();
+        return Err(error);
+    }
+    fixed_literals(clauses)
+}
+
+///pairs the literals fixed by the two probes of a variable, both in ascending order of variable
+/// # Returns
+/// * For every variable fixed by either probe, its value when the variable probed is true and when it is false
+fn merge_probes(fixed_true: &[Literal], fixed_false: &[Literal]) -> Vec<(usize, Option<bool>, Option<bool>)>
+{
+    let (mut t, mut f) = (fixed_true.iter().peekable(), fixed_false.iter().peekable());
+    let mut merged = Vec::with_capacity(fixed_true.len().max(fixed_false.len()));
+    loop
+    {
+        let index = match (t.peek(), f.peek())
+        {
+            (None, None) => return merged,
+            (Some(a), Some(b)) => a.index.min(b.index),
+            (Some(a), None) => a.index,
+            (None, Some(b)) => b.index
+        };
+        let value_true = t.next_if(|l| l.index == index).map(|l| l.value);
+        let value_false = f.next_if(|l| l.index == index).map(|l| l.value);
+        merged.push((index, value_true, value_false));
+    }
+}
+
+///the policies of the searches, set on the Solver
+#[derive(Clone, Copy, Debug, Default)]
+struct Policies
+{
+    restarts: RestartPolicy,
+    phase: PhasePolicy,
+    reduction: ReductionPolicy
+}
+
+///what a search keeps from one branch to the next and across the restarts
+struct SearchState
+{
+    restarts: Restarts,
+    phases: Phases,
+    learned: LearnedClauses,
+    //the implications found by the lookahead under the current decisions
+    implications: BinaryImplications
+}
+
+impl SearchState
+{
+    ///learning tells if the search keeps what it learned from the conflicts across the restarts
+    fn new(policies: Policies, learning: bool) -> SearchState
+    {
+        SearchState{restarts: Restarts::new(policies.restarts, learning), phases: Phases::new(policies.phase), learned: LearnedClauses::new(policies.reduction), implications: BinaryImplications::new()}
+    }
+}
+
+///how a branch of the searches ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome
+{
+    Sat,
+    Unsat,
+    //the restart policy asked to undo every decision, the branch was not refuted
+    Restart
+}
+
+///what a search found at the current decision level
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step
+{
+    Sat,
+    Unsat,
+    //the variable to branch on, with the value the search proposes to try first
+    Branch(Literal)
+}
+
+///runs a search from level 0 again after every restart
+/// # Returns
+/// * true if the search found the formula satisfiable, the propagator then holds the assignments
+fn with_restarts<F>(propagator: &mut Propagator, stats: &mut SolveStats, state: &mut SearchState, mut search: F) -> bool
+    where F: FnMut(&mut Propagator, &mut SolveStats, &mut SearchState) -> Outcome
+{
+    loop
+    {
+        match search(propagator, stats, state)
+        {
+            Outcome::Restart => {
+                debug!("restart {} after {} decisions", state.restarts.restarts() + 1, stats.decisions);
+                propagator.backtrack(0);
+                state.implications.backtrack(propagator);
+                state.restarts.restart();
+                stats.restarts += 1;
+            },
+            outcome => return outcome == Outcome::Sat
+        }
+    }
+}
+
+///Explores the branches of a search depth first without recursion, the stack holds one variable per decision level.\
+///step is called first and after every decision, a variable is tried with the value chosen by the
+///phase policy and then with the other one, and a restart is only taken between the two values,
+///so that a refuted variable is reported as such.
+/// # Arguments
+/// * `step` - Extends the assignments at the current decision level, deciding if they are satisfiable,
+///   refuted, or which variable to branch on
+fn backtracking_search<F>(propagator: &mut Propagator, stats: &mut SolveStats, proof: &mut Option<&mut Proof>, state: &mut SearchState, mut step: F) -> Outcome
+    where F: FnMut(&mut Propagator, &mut SolveStats, &mut Option<&mut Proof>, &mut SearchState) -> Step
+{
+    let base = propagator.decision_level();
+    //the first value tried for every variable branched on, and whether the other one is being tried
+    let mut branches: Vec<(Literal, bool)> = Vec::new();
+    loop
+    {
+        match step(propagator, stats, proof, state)
+        {
+            Step::Sat => return Outcome::Sat,
+            Step::Unsat => {},
+            Step::Branch(choice) => {
+                state.phases.update(propagator);
+                let value = state.phases.value(propagator, choice.index, choice.value);
+                let first = Literal{index: choice.index, value, implicated: false, assigned: false};
+                branch(propagator, stats, first);
+                branches.push((first, false));
+                continue;
             }
-            else if final_phi.clauses[0] == Clause::Empty
+        }
+        //undo the refuted decisions up to the last variable whose other value was not tried
+        loop
+        {
+            let (first, second) = match branches.pop()
             {
-                return None;
-            }
-            else
+                Some(branch) => branch,
+                None => return Outcome::Unsat
+            };
+            derive(proof, propagator, &[]);
+            stats.backtracks += 1;
+            propagator.backtrack(base + branches.len());
+            state.implications.backtrack(propagator);
+            if !second
             {
-                panic!("This should not happen")
+                if state.restarts.should_restart() { return Outcome::Restart; }
+                branch(propagator, stats, first.negated());
+                branches.push((first, true));
+                break;
             }
         }
-        //try to assign it to true
-        assignments[literal] = Some(true);
-        if let Some(assignments_true) = _dpll(&new_phi, assignments.clone())
+    }
+}
+
+///opens a decision level with the literal
+fn branch(propagator: &mut Propagator, stats: &mut SolveStats, lit: Literal)
+{
+    stats.decisions += 1;
+    debug!("deciding x{} = {} at level {}", lit.index + 1, lit.value, propagator.decision_level() + 1);
+    propagator.decide(lit);
+}
+
+///Keeps a literal forced by the lookahead as a learned clause, the literal or the negation of one of the decisions,
+///and assigns it with the clause as its reason so that the clause is kept as long as the literal is assigned
+/// # Returns
+/// * false if the literal is already false
+fn learn_unit(propagator: &mut Propagator, stats: &mut SolveStats, state: &mut SearchState, lit: Literal) -> bool
+{
+    match propagator.value(lit.index)
+    {
+        Some(value) => value == lit.value,
+        None => {
+            let mut clause = vec![lit];
+            clause.extend(propagator.decisions().map(|l| l.negated()));
+            let lbd = learned::lbd(propagator, &clause);
+            state.learned.add(propagator, clause, lbd);
+            stats.learned_clauses += 1;
+            true
+        }
+    }
+}
+
+///Stores an implication found by a probe and logs it to the proof, unless it follows from the stored ones
+fn learn_implication(propagator: &Propagator, stats: &mut SolveStats, proof: &mut Option<&mut Proof>, state: &mut SearchState, from: Literal, to: Literal)
+{
+    if state.implications.add(from, to, propagator.decision_level()){
+        derive(proof, propagator, &[from.negated(), to]);
+        stats.implications += 1;
+    }
+}
+
+fn _dpll(propagator: &mut Propagator, stats: &mut SolveStats, proof: &mut Option<&mut Proof>, state: &mut SearchState) -> Outcome
+{
+    backtracking_search(propagator, stats, proof, state, |propagator, stats, _, state| {
+        if timed_propagate(propagator, stats).is_some()
         {
-            return Some(assignments_true);
+            //nothing is learned, the clause refuting the branch is the negation of every decision
+            state.restarts.on_conflict(propagator.decision_level());
+            return Step::Unsat;
         }
-        //try to assign it to false
-        assignments[literal] = Some(false);
-        if let Some(assignments_false) = _dpll(&new_phi, assignments.clone())
+        //branch on the first variable that is not assigned, true unless its phase says otherwise
+        match propagator.assignments().iter().position(|a| a.is_none())
         {
-            return Some(assignments_false);
+            Some(index) => Step::Branch(Literal{index, value: true, implicated: false, assigned: false}),
+            //every variable is assigned and no clause is falsified
+            None => Step::Sat
         }
-        //if both fail, reset the assignment and return false
-        assignments[literal] = None;
-        return None;
-    }
+    })
 }
 
 pub fn dpll(phi: &Phi) -> Option<Vec<Option<bool>>>
 {
-    let assignments: Vec<Option<bool>> = vec![None;phi.vars()];
-    if let Some(assignments) = _dpll(phi, assignments)
+    dpll_with_stats(phi, &mut SolveStats::new())
+}
+
+pub fn dpll_with_stats(phi: &Phi, stats: &mut SolveStats) -> Option<Vec<Option<bool>>>
+{
+    dpll_search(phi, stats, None, Policies::default())
+}
+
+///Same as dpll_with_stats, also logs to the proof the negation of every failed branch and the empty clause
+pub fn dpll_with_proof(phi: &Phi, stats: &mut SolveStats, proof: &mut Proof) -> Option<Vec<Option<bool>>>
+{
+    dpll_search(phi, stats, Some(proof), Policies::default())
+}
+
+fn dpll_search(phi: &Phi, stats: &mut SolveStats, mut proof: Option<&mut Proof>, policies: Policies) -> Option<Vec<Option<bool>>>
+{
+    let start = Instant::now();
+    let mut propagator = Propagator::new(phi, phi.vars());
+    let mut state = SearchState::new(policies, false);
+    let satisfiable = with_restarts(&mut propagator, stats, &mut state, |propagator, stats, state| _dpll(propagator, stats, &mut proof, state));
+    stats.propagations += propagator.propagations();
+    stats.total_time += start.elapsed();
+    if satisfiable
     {
-        Some(assignments)
+        Some(propagator.assignments().to_vec())
     }
     else
     {
+        derive(&mut proof, &propagator, &[]);
         None
     }
 }
@@ -164,261 +827,643 @@ pub fn dpll(phi: &Phi) -> Option<Vec<Option<bool>>>
     /// * `lit` - The literal to add
 pub fn add_decision(lit: Literal, graph: &mut DiGraph<Literal,Literal>)->NodeIndex{
     let lit = Literal{index: lit.index, value: lit.value, implicated: false, assigned: true};
-    return graph.add_node(lit);
+    graph.add_node(lit)
+}
+
+///add an implication antecedent_1 & ... & antecedent_n -> c to the graph
+/// the antecedents must already be nodes of the graph
+    /// # Arguments
+    /// * `antecedents` - The nodes of the literals that caused the implication
+    /// * `c` - The implicated literal
+    /// * `graph` - A mutable reference to a DiGraph<Literal,Literal>
+pub fn add_reasoned_implication(antecedents: &[NodeIndex], c: Literal, graph: &mut DiGraph<Literal,Literal>)->NodeIndex{
+    let c = Literal{index: c.index, value: c.value, implicated: true, assigned: false};
+    let node = graph.add_node(c);
+    for antecedent in antecedents{
+        graph.add_edge(*antecedent, node, Literal{index:0,value:false,implicated:false, assigned: false});
+    }
+    node
 }
 
+/*
+CDCL search: every assignment is a node of the implication graph, decisions have no incoming edges and
+implied literals point back to the literals that falsified the rest of their reason clause.
+When a clause becomes empty the graph is walked backwards from the conflict to the first unique implication point
+of the current decision level, the resulting clause is learned and the search jumps back to the second highest
+level found in it.
+*/
+struct Cdcl{
+    propagator: Propagator,
+    //the node of every assigned variable, nodes are added in trail order
+    nodes: Vec<Option<NodeIndex>>,
+    //how often each variable took part in recent conflicts, decisions pick the most active one
+    order: VarOrder,
+    graph: DiGraph<Literal,Literal>,
+    //literals decided first, one per level, in incremental searches
+    assumptions: Vec<Literal>,
+    //the assumptions that made the last search fail
+    failed: Vec<Literal>,
+    //a conflict was found at level 0, the formula is unsatisfiable whatever the assumptions
+    unsat: bool,
+    state: SearchState
+}
 
+const ACTIVITY_DECAY: f64 = 0.95;
 
-pub fn update_implication_graph(phi: &Phi, graph:&mut DiGraph<Literal,Literal>){
-    let implications = phi.get_implications();
+///The variables of CDCL on a binary heap ordered by activity, ties broken by the lowest index.\
+///Assigned variables are left in the heap and skipped when they reach the top, the search puts the variables
+///back when it jumps back.
+struct VarOrder{
+    activity: Vec<f64>,
+    bump: f64,
+    heap: Vec<usize>,
+    //the position of every variable in the heap, None if it is not in it
+    positions: Vec<Option<usize>>
+}
 
-    for clause in implications{
-        match clause{
-            Clause::C3(lit1,lit2,lit3)=>{
-                if !lit1.assigned{
-                    let l1 = Literal{index: lit1.index, value: lit1.value, implicated: true, assigned: false};
-                    add_implication(lit2, Some(lit3), l1, graph);
-                }
-                if !lit2.assigned{
-                    let l2 = Literal{index: lit2.index, value: lit2.value, implicated: true, assigned: false};
-                    add_implication(lit1, Some(lit3), l2, graph);
-                }
-                if !lit3.assigned{
-                    let l3 = Literal{index: lit3.index, value: lit3.value, implicated: true, assigned: false};
-                    add_implication(lit1, Some(lit2), l3, graph);
-                }
+impl VarOrder{
+    fn new(n_vars: usize) -> VarOrder{
+        let mut order = VarOrder{activity: Vec::new(), bump: 1.0, heap: Vec::new(), positions: Vec::new()};
+        order.add_vars(n_vars);
+        order
+    }
+
+    ///adds the variables up to n_vars, all of them in the heap
+    fn add_vars(&mut self, n_vars: usize){
+        for index in self.activity.len()..n_vars{
+            self.activity.push(0.0);
+            self.positions.push(None);
+            self.insert(index);
+        }
+    }
+
+    fn before(&self, a: usize, b: usize) -> bool{
+        self.activity[a] > self.activity[b] || (self.activity[a] == self.activity[b] && a < b)
+    }
+
+    fn insert(&mut self, index: usize){
+        if self.positions[index].is_some() {return;}
+        self.positions[index] = Some(self.heap.len());
+        self.heap.push(index);
+        self.sift_up(self.heap.len() - 1);
+    }
+
+    fn swap(&mut self, i: usize, j: usize){
+        self.heap.swap(i, j);
+        self.positions[self.heap[i]] = Some(i);
+        self.positions[self.heap[j]] = Some(j);
+    }
+
+    fn sift_up(&mut self, mut i: usize){
+        while i > 0 && self.before(self.heap[i], self.heap[(i - 1)/2]){
+            self.swap(i, (i - 1)/2);
+            i = (i - 1)/2;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize){
+        loop{
+            let mut best = i;
+            for child in [2*i + 1, 2*i + 2]{
+                if child < self.heap.len() && self.before(self.heap[child], self.heap[best]) {best = child;}
             }
-            Clause::C2(lit1,lit2)=>{
-                if !lit1.assigned{
-                    let l1 = Literal{index: lit1.index, value: lit1.value, implicated: true, assigned: false};
-                    add_implication(lit2, None, l1, graph);
-                }
-                if !lit2.assigned{
-                    let l2 = Literal{index: lit2.index, value: lit2.value, implicated: true, assigned: false};
-                    add_implication(lit1, None, l2, graph);
-                }
+            if best == i {return;}
+            self.swap(i, best);
+            i = best;
+        }
+    }
+
+    ///makes the variable more active, rescaling every activity before they overflow
+    fn bump(&mut self, index: usize){
+        self.activity[index] += self.bump;
+        if self.activity[index] > 1e100{
+            //the order is kept since every activity is scaled by the same factor
+            for a in self.activity.iter_mut(){
+                *a *= 1e-100;
             }
-            _=>{unreachable!()}
+            self.bump *= 1e-100;
         }
+        if let Some(position) = self.positions[index] {self.sift_up(position);}
+    }
+
+    ///makes the conflicts seen so far weigh less than the next ones
+    fn decay(&mut self){
+        self.bump /= ACTIVITY_DECAY;
+    }
+
+    ///removes from the heap and returns the most active unassigned variable
+    fn pop(&mut self, assignments: &[Option<bool>]) -> Option<usize>{
+        while let Some(&top) = self.heap.first(){
+            let last = self.heap.pop().unwrap();
+            self.positions[top] = None;
+            if !self.heap.is_empty(){
+                self.heap[0] = last;
+                self.positions[last] = Some(0);
+                self.sift_down(0);
+            }
+            if assignments[top].is_none() {return Some(top);}
+        }
+        None
     }
 }
 
-pub fn find_conflicts(graph :&DiGraph<Literal,Literal>)->Vec<Literal>{
-    let mut literals_found: HashMap<usize, bool> = HashMap::new(); 
-    let mut conflicts: Vec<Literal> = Vec::new();
-    for node in graph.node_weights(){
-        if node.implicated{
-            if literals_found.contains_key(&node.index){
-                conflicts.push(*node);
+impl Cdcl{
+    fn new(phi: &Phi, policies: Policies) -> Cdcl{
+        let propagator = Propagator::new(phi, phi.vars());
+        let n_vars = propagator.num_vars();
+        Cdcl{
+            propagator,
+            nodes: vec![None;n_vars],
+            order: VarOrder::new(n_vars),
+            graph: DiGraph::<Literal,Literal>::new(),
+            assumptions: Vec::new(),
+            failed: Vec::new(),
+            unsat: false,
+            state: SearchState::new(policies, true)
+        }
+    }
+
+    fn add_vars(&mut self, n_vars: usize){
+        self.propagator.add_vars(n_vars);
+        self.nodes.resize(self.propagator.num_vars(), None);
+        self.order.add_vars(self.propagator.num_vars());
+    }
+
+    ///Adds a clause between two searches, the literals false at level 0 are dropped
+    ///since the watches of a clause must not be false when it is added
+    fn add_clause(&mut self, literals: &[Literal]){
+        self.backjump(0);
+        self.add_vars(literals.iter().map(|l| l.index + 1).max().unwrap_or(0));
+        let mut kept: Vec<Literal> = Vec::with_capacity(literals.len());
+        for lit in literals{
+            match self.propagator.value(lit.index){
+                Some(v) if v == lit.value => return,
+                Some(_) => {},
+                None => {
+                    //a tautology is always satisfied
+                    if kept.iter().any(|l| l.index == lit.index && l.value != lit.value) {return;}
+                    kept.push(Literal{index: lit.index, value: lit.value, implicated: false, assigned: false});
+                }
             }
-            else{
-                literals_found.insert(node.index,true);
+        }
+        self.propagator.add_clause(kept);
+    }
+
+    ///Searches under the given assumptions, keeping the clauses learned by the previous searches
+    /// # Returns
+    /// * The assignments if the formula is satisfiable under the assumptions,
+    ///   otherwise None and the failed assumptions are collected
+    fn solve(&mut self, assumptions: &[Literal], stats: &mut SolveStats, terminate: &mut Option<Box<dyn FnMut() -> bool>>) -> Result<Option<Vec<Option<bool>>>,Error>{
+        self.backjump(0);
+        self.failed.clear();
+        if self.unsat {return Ok(None);}
+        self.add_vars(assumptions.iter().map(|l| l.index + 1).max().unwrap_or(0));
+        self.assumptions = assumptions.to_vec();
+        self.search(stats, &mut None, terminate)
+    }
+
+    ///Collects the assumptions that imply the negation of the given one, the assumption included,
+    ///following the reasons on the trail backwards
+    fn analyze_final(&mut self, assumption: Literal){
+        self.failed = vec![assumption];
+        let mut seen: Vec<bool> = vec![false;self.propagator.num_vars()];
+        seen[assumption.index] = true;
+        for lit in self.propagator.trail().iter().rev(){
+            if !seen[lit.index] || self.propagator.level(lit.index) == 0 {continue;}
+            match self.propagator.reason(lit.index){
+                None => self.failed.push(Literal{index: lit.index, value: lit.value, implicated: false, assigned: false}),
+                Some(reason) => {
+                    for l in self.propagator.clause(reason){
+                        seen[l.index] = true;
+                    }
+                }
             }
         }
     }
-    conflicts
-}
 
-///add an implication a&b -> c to the graph
-/// if b is none, adds a -> c to the graph
-    /// # Arguments
-    /// * `graph` - A mutable reference to a DiGraph<Literal,Literal>
-    /// * `a` - The first literal
-    /// * `b` - The optional second literal
-    /// * `c` - The implicated literal
-pub fn add_implication(a: Literal, b: Option<Literal>, c: Literal, graph: &mut DiGraph<Literal,Literal>)->(NodeIndex,Option<NodeIndex>,NodeIndex){
-    
-    if !graph.node_weights().any(|x| (x.index == a.index && x.value == a.value)){
-        graph.add_node(a);
+    ///adds to the graph a node for every assignment on the trail that does not have one yet
+    fn update_graph(&mut self){
+        while self.graph.node_count() < self.propagator.trail().len(){
+            let lit = self.propagator.trail()[self.graph.node_count()];
+            let node = match self.propagator.reason(lit.index){
+                None => add_decision(lit, &mut self.graph),
+                Some(reason) => {
+                    let antecedents: Vec<NodeIndex> = self.propagator.clause(reason).iter()
+                        .filter(|l| l.index != lit.index)
+                        .map(|l| self.nodes[l.index].unwrap())
+                        .collect();
+                    add_reasoned_implication(&antecedents, lit, &mut self.graph)
+                }
+            };
+            self.nodes[lit.index] = Some(node);
+        }
     }
 
-    if b.is_some(){
-        let _b= b.unwrap();
-        if !graph.node_weights().any(|x| (x.index == _b.index && x.value == _b.value)){
-            graph.add_node(_b);
+    ///derives the first-UIP clause of a conflict by walking the implication graph backwards
+    /// # Returns
+    /// * The learned clause, with the asserting literal in first position and the literal of the
+    ///   level to jump back to in second position, and that level
+    fn conflict_to_clause(&mut self, conflict: usize) -> (Vec<Literal>, usize){
+        let level = self.propagator.decision_level();
+        let mut seen: Vec<bool> = vec![false;self.propagator.num_vars()];
+        let mut learned: Vec<Literal> = Vec::new();
+        let mut pending = 0;
+
+        self.state.learned.bump(conflict);
+        let mut antecedents: Vec<usize> = self.propagator.clause(conflict).iter().map(|l| l.index).collect();
+        let mut position = self.propagator.trail().len();
+        let uip = loop{
+            for index in antecedents{
+                if seen[index] || self.propagator.level(index) == 0 {continue;}
+                seen[index] = true;
+                self.order.bump(index);
+                if self.propagator.level(index) == level{
+                    pending += 1;
+                }
+                else{
+                    learned.push(Literal{index, value: !self.propagator.value(index).unwrap(), implicated: false, assigned: false});
+                }
+            }
+            //go back to the most recent literal of the current level that takes part in the conflict
+            loop{
+                position -= 1;
+                if seen[self.propagator.trail()[position].index] {break;}
+            }
+            let index = self.propagator.trail()[position].index;
+            pending -= 1;
+            if pending == 0{
+                break index;
+            }
+            if let Some(reason) = self.propagator.reason(index){
+                self.state.learned.bump(reason);
+            }
+            antecedents = self.graph.neighbors_directed(self.nodes[index].unwrap(), Incoming)
+                .map(|n| self.graph[n].index)
+                .collect();
+        };
+        learned.insert(0, Literal{index: uip, value: !self.propagator.value(uip).unwrap(), implicated: false, assigned: false});
+
+        //the second literal is watched too, it must be the last one to be unassigned when jumping back
+        let mut backjump = 0;
+        for i in 1..learned.len(){
+            let l = self.propagator.level(learned[i].index);
+            if l > backjump{
+                backjump = l;
+                learned.swap(1, i);
+            }
         }
+        (learned, backjump)
     }
-    if !graph.node_weights().any(|x| (x == &c && x.value == c.value)){
-        graph.add_node(c);
+
+    fn pick_branching_variable(&mut self) -> Option<usize>{
+        self.order.pop(self.propagator.assignments())
     }
 
-    let node1 = graph.node_indices().find(|i| (graph[*i].index,graph[*i].value)== (a.index,a.value)).unwrap();
-    let mut node2: Option<NodeIndex> = None;
-    if b.is_some(){let _b = b.unwrap();node2 = Some(graph.node_indices().find(|i| (graph[*i].index,graph[*i].value)== (_b.index,_b.value)).unwrap());}
-    let node3 = graph.node_indices().find(|i| (graph[*i].index,graph[*i].value) == (c.index,c.value)).unwrap();
+    fn backjump(&mut self, level: usize){
+        //the variables unassigned go back in the heap, the levels grow along the trail
+        for lit in self.propagator.trail().iter().rev(){
+            if self.propagator.level(lit.index) <= level {break;}
+            self.order.insert(lit.index);
+        }
+        self.propagator.backtrack(level);
+        //nodes are added in trail order, so the removed node is always the last one and no index is invalidated
+        while self.graph.node_count() > self.propagator.trail().len(){
+            let node = NodeIndex::new(self.graph.node_count() - 1);
+            let index = self.graph.remove_node(node).unwrap().index;
+            self.nodes[index] = None;
+        }
+    }
 
-    if node2.is_some(){
-        graph.add_edge(node2.unwrap(),node3,Literal{index:0,value:false,implicated:false, assigned: false});
+    fn search(&mut self, stats: &mut SolveStats, proof: &mut Option<&mut Proof>, terminate: &mut Option<Box<dyn FnMut() -> bool>>) -> Result<Option<Vec<Option<bool>>>,Error>{
+        loop{
+            let conflict = timed_propagate(&mut self.propagator, stats);
+            self.update_graph();
+            match conflict{
+                Some(conflict) => {
+                    if self.propagator.decision_level() == 0{
+                        derive(proof, &self.propagator, &[]);
+                        self.unsat = true;
+                        return Ok(None);
+                    }
+                    if terminate.as_mut().is_some_and(|terminate| terminate()){
+                        debug!("search terminated after {} conflicts", stats.backtracks);
+                        return Err(Error::Terminated);
+                    }
+                    let start = Instant::now();
+                    let (learned, level) = self.conflict_to_clause(conflict);
+                    stats.analysis_time += start.elapsed();
+                    trace!("learned {} jumping back to level {}", Clause::new(learned.clone()), level);
+                    if let Some(proof) = proof{
+                        proof.add(&learned);
+                    }
+                    self.order.decay();
+                    //the levels are counted before jumping back, when every literal is false
+                    let lbd = learned::lbd(&self.propagator, &learned);
+                    self.state.restarts.on_conflict(lbd);
+                    self.state.learned.decay();
+                    stats.backtracks += 1;
+                    self.backjump(level);
+                    //the asserting literal is assigned with the learned clause as its reason
+                    self.state.learned.add(&mut self.propagator, learned, lbd);
+                    stats.learned_clauses += 1;
+                    stats.deleted_clauses += self.state.learned.reduce(&mut self.propagator, proof) as u64;
+                },
+                None => {
+                    //the learned clauses are kept, so the assumptions are simply decided again
+                    if self.state.restarts.should_restart(){
+                        debug!("restart {} after {} conflicts", self.state.restarts.restarts() + 1, stats.backtracks);
+                        self.state.restarts.restart();
+                        stats.restarts += 1;
+                        self.backjump(0);
+                        continue;
+                    }
+                    if let Some(&assumption) = self.assumptions.get(self.propagator.decision_level()){
+                        if self.propagator.value(assumption.index) == Some(!assumption.value){
+                            self.analyze_final(assumption);
+                            return Ok(None);
+                        }
+                        //an assumption already true opens an empty level, so that every assumption has its own level
+                        self.propagator.decide(assumption);
+                        continue;
+                    }
+                    match self.pick_branching_variable(){
+                        Some(index) => {
+                            stats.decisions += 1;
+                            self.state.phases.update(&self.propagator);
+                            let value = self.state.phases.value(&self.propagator, index, true);
+                            self.propagator.decide(Literal{index, value, implicated: false, assigned: false});
+                        },
+                        None => return Ok(Some(self.propagator.assignments().to_vec()))
+                    }
+                }
+            }
+        }
     }
-    graph.add_edge(node1,node3,Literal{index:0,value:false,implicated:false, assigned: false});
-    (node1,node2,node3)
 }
 
+///Conflict driven clause learning: returns a satisfying assignment for phi if it exists, None otherwise
+pub fn cdcl(phi: &Phi) -> Option<Vec<Option<bool>>>
+{
+    cdcl_with_stats(phi, &mut SolveStats::new())
+}
+
+pub fn cdcl_with_stats(phi: &Phi, stats: &mut SolveStats) -> Option<Vec<Option<bool>>>
+{
+    cdcl_search(phi, stats, None, Policies::default())
+}
+
+///Same as cdcl_with_stats, also logs to the proof every learned clause and the empty clause
+pub fn cdcl_with_proof(phi: &Phi, stats: &mut SolveStats, proof: &mut Proof) -> Option<Vec<Option<bool>>>
+{
+    cdcl_search(phi, stats, Some(proof), Policies::default())
+}
 
+fn cdcl_search(phi: &Phi, stats: &mut SolveStats, mut proof: Option<&mut Proof>, policies: Policies) -> Option<Vec<Option<bool>>>
+{
+    let start = Instant::now();
+    let mut cdcl = Cdcl::new(phi, policies);
+    //without a terminate callback the search always ends with an answer
+    let solution = cdcl.search(stats, &mut proof, &mut None).ok().flatten();
+    stats.propagations += cdcl.propagator.propagations();
+    stats.total_time += start.elapsed();
+    solution
+}
 
 pub fn solve(phi: &Phi) -> Option<Vec<bool>>
 {
-    let n_vars = phi.vars();
-    let mut assignment: Vec<Option<bool>> = vec![None;n_vars];
-    let mut phi = phi.clone();
+    solve_with_stats(phi, &mut SolveStats::new())
+}
+
+pub fn solve_with_stats(phi: &Phi, stats: &mut SolveStats) -> Option<Vec<bool>>
+{
+    lookahead_search(phi, stats, None, &mut *Heuristic::default().create(0), Policies::default())
+}
+
+///Same as solve_with_stats, also logs to the proof the literals and the implications found by the lookahead,
+///the negation of every failed branch and the empty clause
+pub fn solve_with_proof(phi: &Phi, stats: &mut SolveStats, proof: &mut Proof) -> Option<Vec<bool>>
+{
+    lookahead_search(phi, stats, Some(proof), &mut *Heuristic::default().create(0), Policies::default())
+}
 
-    while phi.clauses.len() > 0{
-        phi = phi.autoreduce_with_assignments(&mut assignment);
-        //check if phi is empty
-        if phi.clauses.len() > 0 && phi.clauses[0] == Clause::Empty{
-            return None;
+///Same as solve_with_stats, branching on the variables chosen by the heuristic
+pub fn solve_with_heuristic(phi: &Phi, stats: &mut SolveStats, heuristic: &mut dyn BranchingHeuristic) -> Option<Vec<bool>>
+{
+    lookahead_search(phi, stats, None, heuristic, Policies::default())
+}
+
+fn lookahead_search(phi: &Phi, stats: &mut SolveStats, mut proof: Option<&mut Proof>, heuristic: &mut dyn BranchingHeuristic, policies: Policies) -> Option<Vec<bool>>
+{
+    let start = Instant::now();
+    let mut propagator = Propagator::new(phi, phi.vars());
+    let mut state = SearchState::new(policies, false);
+    let satisfiable = with_restarts(&mut propagator, stats, &mut state, |propagator, stats, state| {
+        lookahead(phi, propagator, stats, &mut proof, heuristic, state)
+    });
+    stats.propagations += propagator.propagations();
+    stats.total_time += start.elapsed();
+    if satisfiable{
+        //return the assignment vector, if some value is still none fill it with false
+        Some(propagator.assignments().iter().map(|x| x.unwrap_or(false)).collect())
+    }
+    else{
+        derive(&mut proof, &propagator, &[]);
+        None
+    }
+}
+
+///runs lookahead rounds on the clauses of the formula that the assignments of the propagator do not satisfy,
+///branching when a round finds no forced literal.\
+///Every literal found and every implication stored is logged to the proof as a clause implied under the current decisions.
+/// # Returns
+/// * Sat if the current assignments can be extended to satisfy the formula, the propagator then holds them
+fn lookahead(original: &Phi, propagator: &mut Propagator, stats: &mut SolveStats, proof: &mut Option<&mut Proof>, heuristic: &mut dyn BranchingHeuristic, state: &mut SearchState) -> Outcome
+{
+    backtracking_search(propagator, stats, proof, state, |propagator, stats, proof, state| {
+        lookahead_rounds(original, propagator, stats, proof, heuristic, state)
+    })
+}
+
+///runs lookahead rounds at the current decision level until the assignments are satisfiable or refuted,
+///or a round forces no literal and the heuristic picks the variable to branch on
+fn lookahead_rounds(original: &Phi, propagator: &mut Propagator, stats: &mut SolveStats, proof: &mut Option<&mut Proof>, heuristic: &mut dyn BranchingHeuristic, state: &mut SearchState) -> Step
+{
+    let n_vars = propagator.num_vars();
+    loop{
+        //the implications of the literals assigned assign more literals, which are propagated again
+        loop{
+            if let Some(conflict) = timed_propagate(propagator, stats){
+                heuristic.on_conflict(propagator.clause(conflict));
+                state.restarts.on_conflict(propagator.decision_level());
+                state.learned.bump(conflict);
+                state.learned.decay();
+                return Step::Unsat;
+            }
+            match state.implications.propagate(propagator){
+                Ok(0) => break,
+                Ok(_) => {},
+                Err(lit) => {
+                    heuristic.on_conflict(&[lit]);
+                    stats.conflicts += 1;
+                    state.restarts.on_conflict(propagator.decision_level());
+                    state.learned.decay();
+                    return Step::Unsat;
+                }
+            }
+        }
+        //after the propagation, a clause of the formula that is not satisfied is watched by its unassigned literals,
+        //the first one of them lists it once
+        let mut remaining = vec![false; n_vars];
+        let mut unsatisfied: Vec<usize> = Vec::new();
+        for index in (0..n_vars).filter(|i| propagator.value(*i).is_none()){
+            for value in [true, false]{
+                for c in propagator.watchers(&Literal{index, value, implicated: false, assigned: false}){
+                    let clause = propagator.clause(*c);
+                    if state.learned.lbd(*c).is_none() && !clause.iter().any(|l| propagator.value(l.index) == Some(l.value)){
+                        for l in clause.iter().filter(|l| propagator.value(l.index).is_none()){
+                            remaining[l.index] = true;
+                        }
+                        if (clause[0].index, clause[0].value) == (index, value) { unsatisfied.push(*c); }
+                    }
+                }
+            }
+        }
+        //every clause of the formula is satisfied
+        if !remaining.contains(&true){
+            return Step::Sat;
         }
 
         let mut added_unit_clause: bool = false;
+        let mut conflict: bool = false;
+        let round_start = Instant::now();
+        let trail_len = propagator.trail().len();
+        let (mut branch_true, mut branch_false) = (Vec::new(), Vec::new());
         //for each variable, check if it is forced to be true or false
-        let literals = phi.get_variables();
-        //for every literal in literals that isnt in the reserve list
-        for literal in literals{
-            let phi_prime = phi.phi_prime(literal);
-            assignment[literal] = Some(true);
-            let phi_prime_true: Phi = phi_prime.reduce(&assignment);
-            let solution_true: Result<Vec<Option<bool>>, Error> = solve_2_sat(&phi_prime_true,n_vars);
-
-            assignment[literal] = Some(false);
-            let phi_prime_false: Phi = phi_prime.reduce(&assignment);
-            let solution_false: Result<Vec<Option<bool>>, Error> = solve_2_sat(&phi_prime_false,n_vars);
-            assignment[literal] = None;
+        let literals = (0..n_vars).filter(|i| remaining[*i]);
+        'probe: for literal in literals{
+            //it may have been forced by a previous literal of this round
+            if propagator.value(literal).is_some() {continue;}
+            stats.lookahead_calls += 1;
+            //the 2-SAT formulas of the two values, with the consequences of the implications already known
+            let two_sat_start = Instant::now();
+            //only the values shared by every model of the branch are implied
+            let solution_true = probe(original, propagator, &mut state.implications, Literal{index: literal, value: true, implicated: false, assigned: false}, &mut branch_true);
+            let solution_false = probe(original, propagator, &mut state.implications, Literal{index: literal, value: false, implicated: false, assigned: false}, &mut branch_false);
+            stats.two_sat_calls += 2;
+            stats.two_sat_time += two_sat_start.elapsed();
             match (solution_true,solution_false){
                 (Ok(solution_t),Ok(solution_f)) => {
-                    for (i,(lit_t,lit_f)) in solution_t.iter().zip(solution_f.iter()).enumerate(){
+                    heuristic.on_probe(literal, solution_t.len(), solution_f.len());
+                    for (i,lit_t,lit_f) in merge_probes(&solution_t, &solution_f){
                         match (lit_t,lit_f){
                             (Some(l1),Some(l2)) if l1==l2 => {
-                                let clause = Clause::C1(Literal{index: i,value:*l1,implicated:true, assigned: false});
-                                //phi.clauses.push(clause);
-                                phi.update_implications(&clause);
-                                assignment[i] = Some(*l1);
+                                let lit = Literal{index: i,value:l1,implicated:true, assigned: false};
+                                //the literal is implied by both values of the probed variable
+                                for value in [true, false]{
+                                    derive(proof, propagator, &[Literal{index: literal, value: !value, implicated: false, assigned: false}, lit]);
+                                }
+                                derive(proof, propagator, &[lit]);
+                                if !learn_unit(propagator, stats, state, lit){
+                                    heuristic.on_conflict(&[lit]);
+                                    conflict = true;
+                                    break 'probe;
+                                }
                                 added_unit_clause = true;
                             },
                             (l1,l2) => {
-                                match l1{
-                                    Some(l1) => {
-                                        let clause = Implication{
-                                            from: Literal{index: literal, value: true,implicated: true, assigned: false}, 
-                                            to: Literal{index: i, value: *l1, implicated: true, assigned: false}
-                                        }.to_clause();
-                                        //phi.clauses.push(clause);
+                                for (value, implied) in [(true, l1), (false, l2)]{
+                                    if let Some(implied) = implied{
+                                        learn_implication(propagator, stats, proof, state,
+                                            Literal{index: literal, value, implicated: false, assigned: false},
+                                            Literal{index: i, value: implied, implicated: false, assigned: false});
                                     }
-                                    None => {}
-                                }
-                                match l2{
-                                    Some(l2) => {
-                                        let clause = Implication{
-                                            from: Literal{index: literal, value: false,implicated: true, assigned: false}, 
-                                            to: Literal{index: i, value: *l2, implicated:true, assigned: false}
-                                        }.to_clause();
-                                        //phi.clauses.push(clause);
-                                    }
-                                    None => {}
                                 }
                             }
                         }
                     }
                 },
                 (Ok(solution_t),Err(_)) => {
-                    let clause = Clause::C1(Literal{index: literal,value:true, implicated: true, assigned: false});
-                    //phi.clauses.push(clause);
-                    phi.update_implications(&clause);
-                    assignment[literal] = Some(true);
+                    let lit = Literal{index: literal,value:true, implicated: true, assigned: false};
+                    derive_refuted(proof, propagator, &branch_false, lit);
+                    if !learn_unit(propagator, stats, state, lit){
+                        heuristic.on_conflict(&[lit]);
+                        conflict = true;
+                        break 'probe;
+                    }
                     added_unit_clause = true;
-                    for (index,value) in solution_t.iter().enumerate(){
-                        match value{
-                            Some(value) => {
-                                let clause = Implication{
-                                    from: Literal{index: literal, value: true, implicated: true, assigned: false}, 
-                                    to: Literal{index: index, value: *value, implicated:true, assigned: false}
-                                }.to_clause();
-                                //phi.clauses.push(clause);
-                            }
-                            None => {}
-                        }
+                    //the literal is assigned, the store assigns what it implies in the next propagation
+                    for implied in solution_t{
+                        learn_implication(propagator, stats, proof, state,
+                            Literal{index: literal, value: true, implicated: false, assigned: false},
+                            implied);
                     }
                 },
                 (Err(_),Ok(solution_f)) => {
-                    let clause = Clause::C1(Literal{index: literal, value: false, implicated: true, assigned: false});
-                    //phi.clauses.push(clause);
-                    phi.update_implications(&clause);
-                    assignment[literal] = Some(false);
+                    let lit = Literal{index: literal, value: false, implicated: true, assigned: false};
+                    derive_refuted(proof, propagator, &branch_true, lit);
+                    if !learn_unit(propagator, stats, state, lit){
+                        heuristic.on_conflict(&[lit]);
+                        conflict = true;
+                        break 'probe;
+                    }
                     added_unit_clause = true;
-                    for (index,value) in solution_f.iter().enumerate(){
-                        match value{
-                            Some(value) => {
-                                let clause = Implication{
-                                    from: Literal{index: literal, value: false, implicated:false, assigned: false}, 
-                                    to: Literal{index: index, value: *value, implicated: true, assigned: false}
-                                }.to_clause();
-                                //phi.clauses.push(clause);
-                            }
-                            None => {}
-                        }
+                    for implied in solution_f{
+                        learn_implication(propagator, stats, proof, state,
+                            Literal{index: literal, value: false, implicated: false, assigned: false},
+                            implied);
                     }
                 },
                 (Err(_),Err(_)) => {
-                    return None;
+                    heuristic.on_conflict(&[Literal{index: literal, value: true, implicated: false, assigned: false}]);
+                    derive_refuted(proof, propagator, &branch_true, Literal{index: literal, value: false, implicated: false, assigned: false});
+                    derive_refuted(proof, propagator, &branch_false, Literal{index: literal, value: true, implicated: false, assigned: false});
+                    conflict = true;
+                    break 'probe;
                 },
             }
         }
-        println!("{phi}");
-        if phi.clauses.len() ==1{
-            println!("{:?}",phi.clauses[0]);
+        stats.lookahead_time += round_start.elapsed();
+        stats.implied_per_round.push(propagator.trail().len() - trail_len);
+        debug!("lookahead round at level {}: {} literals implied", propagator.decision_level(), propagator.trail().len() - trail_len);
+        if conflict{
+            debug!("lookahead conflict at level {}", propagator.decision_level());
+            stats.conflicts += 1;
+            state.restarts.on_conflict(propagator.decision_level());
+            state.learned.decay();
+            return Step::Unsat;
         }
+        stats.deleted_clauses += state.learned.reduce(propagator, proof) as u64;
+        trace!("assignments = {:?}", propagator.assignments());
         if !added_unit_clause
         {
-            //if no literal is forced to be true or false, choose one and backtrack
-            let literals = phi.get_variables();
-            if literals.len() > 0{
-                let literal = literals[0];
-                assignment[literal] = Some(true);
-                let phi_true = phi.reduce(&assignment);
-                let result_true = solve(&phi_true);
-                match result_true{
-                    Some(_) => {
-                        //merge result true with assignment
-                        for (index,value) in result_true.unwrap().into_iter().enumerate(){
-                            
-                            assignment[index] = Some(value);
-                                
-                        }
-                        return Some(assignment.iter().map(|x| x.unwrap_or(false)).collect());
-                    },
-                    None => {
-                        assignment[literal] = Some(false);
-                        let phi_false = phi.reduce(&assignment);
-                        let result_false = solve(&phi_false);
-                        match result_false{
-                            Some(_) => {
-                                //merge result false with assignment
-                                for (index,value) in result_false.unwrap().into_iter().enumerate(){
-                                    assignment[index] = Some(value);
-                                }
-                                return Some(assignment.iter().map(|x| x.unwrap_or(false)).collect());
-                            },
-                            None => {
-                                return None;
-                            }
-                        }
-                    }
+            //if no literal is forced to be true or false, let the heuristic choose one to branch on,
+            //the clauses are passed in the order of the formula
+            unsatisfied.sort_unstable();
+            let clauses: Vec<&[Literal]> = unsatisfied.iter().map(|c| propagator.clause(*c)).collect();
+            let variables: Vec<usize> = (0..n_vars).filter(|i| remaining[*i]).collect();
+            let choice = heuristic.pick(&clauses, &variables, propagator.assignments());
+            return match choice{
+                Some(choice) if choice.index < n_vars && remaining[choice.index] && propagator.value(choice.index).is_none() => Step::Branch(choice),
+                _ => {
+                    debug!("the heuristic picked {:?}, not a variable of the clauses left, branching on x{}", choice, variables[0] + 1);
+                    Step::Branch(Literal{index: variables[0], value: true, implicated: false, assigned: false})
                 }
-            }
+            };
         }
     }
-    //return the assignment vector, if some value is still none fill it with false
-    Some(assignment.iter().map(|x| x.unwrap_or(false)).collect())
 }
 
-
-
-
 #[cfg(test)]
 mod tests
 {
-    use crate::phi;
     use crate::phi::*;
     use crate::solver::*;
+    use crate::drat::ProofFormat;
+    use crate::proof::check_drat;
+    use std::{fs::File, io::BufReader};
+
+    ///a search of the solver that writes a proof
+    type Search = fn(&Solver, &mut Proof)->Result<(Option<Vec<isize>>, SolveStats), Error>;
     
 
     #[test]
@@ -430,18 +1475,287 @@ mod tests
         println!("{:?}",result);
     }
 
+    #[test]
+    fn solve_wide()
+    {
+        let solver = Solver::create("TestData/wide.cnf").unwrap();
+        let result = solver.solve().unwrap().unwrap();
+        let assignment: Vec<bool> = result.iter().map(|x| *x > 0).collect();
+        assert!(solver.phi.eval(&assignment));
+    }
+
+    #[test]
+    fn solve_3sat_conversion()
+    {
+        let original = Phi::from_file("TestData/wide.cnf").unwrap();
+        let solver = Solver::create_3sat("TestData/wide.cnf").unwrap();
+        assert!(solver.phi.is_3_sat());
+        assert!(solver.num_variables() > original.vars());
+        assert_eq!(solver.solve().unwrap().unwrap().len(), original.vars());
+        let result = solver.solve_cdcl().unwrap().unwrap();
+        assert_eq!(result.len(), original.vars());
+        let assignment: Vec<bool> = result.iter().map(|x| *x > 0).collect();
+        assert!(original.eval(&assignment));
+    }
+
+    #[test]
+    fn var_order()
+    {
+        let mut order = VarOrder::new(5);
+        for index in [3, 1, 3]
+        {
+            order.bump(index);
+        }
+        order.decay();
+        order.bump(4);
+        //x5 was bumped after the decay, ties go to the lowest index
+        let mut assignments = vec![None; 5];
+        assignments[3] = Some(true);
+        assert_eq!(order.pop(&assignments), Some(4));
+        assert_eq!(order.pop(&assignments), Some(1));
+        assert_eq!(order.pop(&assignments), Some(0));
+        //x4 is assigned, it leaves the heap and comes back once unassigned
+        assignments[3] = None;
+        assert_eq!(order.pop(&assignments), Some(2));
+        assert_eq!(order.pop(&assignments), None);
+        order.insert(0);
+        order.insert(3);
+        order.add_vars(6);
+        assignments.push(None);
+        let picks: Vec<Option<usize>> = (0..4).map(|_| order.pop(&assignments)).collect();
+        assert_eq!(picks, vec![Some(3), Some(0), Some(5), None]);
+    }
+
+    #[test]
+    fn deep_search()
+    {
+        //every variable is a decision, the search goes as deep as there are variables
+        let n = 20_000;
+        let phi = Phi::from_clauses((1..n - 1).map(|i| Clause::new_c3(i, i + 1, -(i + 2))).collect());
+        let mut stats = SolveStats::new();
+        let result = super::dpll_with_stats(&phi, &mut stats).unwrap();
+        assert_eq!(phi.falsified(&result.iter().map(|v| v.unwrap()).collect::<Vec<bool>>()), None);
+        assert_eq!((stats.decisions, stats.conflicts), (n as u64, 0));
+    }
+
     #[test]
     fn solve_20()
     {
-        let mut bad_results = 0;
+        let phi = Phi::from_file("TestData/solver20-0.cnf").unwrap();
         for _ in 0..100
         {
-            let phi = Phi::from_file("TestData/solver20-0.cnf").unwrap();
-            let result = super::solve(&phi);
-            result.unwrap_or_else(||{bad_results+=1;vec![]});
+            let result = super::solve(&phi).expect("solver20-0 is satisfiable");
+            assert_eq!(phi.falsified(&result), None);
+        }
+    }
+
+    #[test]
+    fn verify()
+    {
+        let mut solver = Solver::create_3sat("TestData/wide.cnf").unwrap();
+        solver.set_verify(true);
+        assert!(solver.solve().unwrap().is_some());
+        assert!(solver.solve_dpll().unwrap().is_some());
+        assert!(solver.solve_cdcl().unwrap().is_some());
+
+        let solver = Solver::from_reader("p cnf 2 2\n1 2 0\n-1 2 0\n".as_bytes()).unwrap();
+        assert!(solver.check_model(&[false, true]).is_ok());
+        let e = solver.check_model(&[true, false]).unwrap_err();
+        assert!(matches!(e, Error::ModelFalsified{index: 1, ..}));
+        assert_eq!(e.to_string(), "internal error: the model falsifies the clause 1 ( -1 2 )");
+    }
+
+    #[test]
+    fn heuristics()
+    {
+        let mut sat = Solver::create("TestData/uf50-01.cnf").unwrap();
+        let mut unsat = Solver::create("TestData/unsat30.cnf").unwrap();
+        for heuristic in [Heuristic::Vsids, Heuristic::JeroslowWang, Heuristic::Moms, Heuristic::Lookahead]
+        {
+            for solver in [&mut sat, &mut unsat]
+            {
+                solver.set_heuristic(heuristic);
+                solver.set_seed(11);
+            }
+            let (model, stats) = sat.solve_with_stats().unwrap();
+            assert_eq!(sat.phi.falsified(&model.unwrap().iter().map(|l| *l > 0).collect::<Vec<bool>>()), None);
+            //the same seed makes the same choices
+            let (again, same) = sat.solve_with_stats().unwrap();
+            assert!(again.is_some());
+            assert_eq!((stats.decisions, stats.lookahead_calls), (same.decisions, same.lookahead_calls));
+            assert!(unsat.solve().unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn restarts()
+    {
+        let path = std::env::temp_dir().join(format!("colombini_sat_restarts_{}.drat", std::process::id()));
+        let path = path.to_str().unwrap();
+        let searches: [Search; 3] =
+            [Solver::solve_with_proof, Solver::solve_dpll_with_proof, Solver::solve_cdcl_with_proof];
+        let policies = [RestartPolicy::Luby(1), RestartPolicy::Geometric{first: 1, factor: 1.5}, RestartPolicy::Glucose];
+        let mut sat = Solver::create("TestData/uf50-01.cnf").unwrap();
+        let mut unsat = Solver::create("TestData/unsat30.cnf").unwrap();
+        sat.set_verify(true);
+        for (policy, phase) in policies.into_iter().zip([PhasePolicy::Saved, PhasePolicy::Target, PhasePolicy::Fixed])
+        {
+            for solver in [&mut sat, &mut unsat]
+            {
+                solver.set_restarts(policy).unwrap();
+                solver.set_phase(phase);
+            }
+            for search in searches
+            {
+                assert!(search(&sat, &mut Proof::new(std::io::sink(), ProofFormat::Binary)).unwrap().0.is_some());
+                //the branches refuted before a restart are still valid lemmas of the proof
+                let mut proof = Proof::create(path, ProofFormat::Text).unwrap();
+                let (solution, stats) = search(&unsat, &mut proof).unwrap();
+                assert!(solution.is_none());
+                assert!(stats.restarts > 0 || policy == RestartPolicy::Glucose, "{policy:?} never restarted");
+                proof.finish().unwrap();
+                let core = check_drat(&unsat.phi, BufReader::new(File::open(path).unwrap()), ProofFormat::Text).unwrap();
+                assert_eq!(core.lemmas.last(), Some(&Clause::empty()));
+            }
+        }
+        std::fs::remove_file(path).unwrap();
+
+        //a backbone instance, where the early decisions are often wrong
+        let mut backbone = Solver::create("TestData/CBS_k3_n100_m449_b90_0.cnf").unwrap();
+        backbone.set_verify(true);
+        backbone.set_restarts(RestartPolicy::Glucose).unwrap();
+        backbone.set_phase(PhasePolicy::Target);
+        assert!(backbone.solve_cdcl().unwrap().is_some());
+
+        //without restarts the searches are unchanged
+        sat.set_restarts(RestartPolicy::Never).unwrap();
+        sat.set_phase(PhasePolicy::Fixed);
+        let (_, stats) = sat.solve_dpll_with_stats().unwrap();
+        let mut plain = SolveStats::new();
+        dpll_with_stats(&sat.phi, &mut plain);
+        assert_eq!((stats.restarts, stats.decisions), (0, plain.decisions));
+    }
+
+    #[test]
+    fn learned_clauses()
+    {
+        let path = std::env::temp_dir().join(format!("colombini_sat_learned_{}.drat", std::process::id()));
+        let path = path.to_str().unwrap();
+        let searches: [Search; 2] = [Solver::solve_cdcl_with_proof, Solver::solve_with_proof];
+        let mut sat = Solver::create("TestData/uf50-01.cnf").unwrap();
+        let mut unsat = Solver::create("TestData/unsat30.cnf").unwrap();
+        sat.set_verify(true);
+        for reduction in [ReductionPolicy::Lbd{limit: 4, increment: 1}, ReductionPolicy::Activity{limit: 4, increment: 1}, ReductionPolicy::Never]
+        {
+            for solver in [&mut sat, &mut unsat]
+            {
+                solver.set_reduction(reduction);
+                solver.set_restarts(RestartPolicy::Luby(8)).unwrap();
+            }
+            for (i, search) in searches.into_iter().enumerate()
+            {
+                assert!(search(&sat, &mut Proof::new(std::io::sink(), ProofFormat::Binary)).unwrap().0.is_some());
+                //the deletions are logged, the lemmas derived after them do not need the clauses deleted
+                let mut proof = Proof::create(path, ProofFormat::Text).unwrap();
+                let (solution, stats) = search(&unsat, &mut proof).unwrap();
+                assert!(solution.is_none());
+                assert!(stats.learned_clauses > 0);
+                assert_eq!(proof.deletions(), stats.deleted_clauses);
+                //the few literals forced by the lookahead on unsat30 are mostly binary clauses, which are kept
+                if i == 0 || reduction == ReductionPolicy::Never
+                {
+                    assert_eq!(stats.deleted_clauses == 0, reduction == ReductionPolicy::Never);
+                }
+                proof.finish().unwrap();
+                let core = check_drat(&unsat.phi, BufReader::new(File::open(path).unwrap()), ProofFormat::Text).unwrap();
+                assert_eq!(core.lemmas.last(), Some(&Clause::empty()));
+            }
+        }
+        std::fs::remove_file(path).unwrap();
+
+        //the literals forced by the lookahead deep in the search are deleted too
+        let mut deep = Solver::create("TestData/solver0.cnf").unwrap();
+        deep.set_verify(true);
+        deep.set_reduction(ReductionPolicy::Activity{limit: 4, increment: 1});
+        let (solution, stats) = deep.solve_with_stats().unwrap();
+        assert!(solution.is_some() && stats.deleted_clauses > 0);
+
+        //the learned clauses are reduced across the incremental searches
+        let mut incremental = Solver::create("TestData/unsat30.cnf").unwrap();
+        incremental.set_reduction(ReductionPolicy::Lbd{limit: 4, increment: 1});
+        assert!(incremental.solve_with_assumptions(&[1]).unwrap().is_none());
+        assert!(incremental.solve_with_assumptions(&[-1]).unwrap().is_none());
+    }
+
+    #[test]
+    fn incremental()
+    {
+        //every model found is blocked by the next clause
+        let mut solver = Solver::create("TestData/uf50-01.cnf").unwrap();
+        solver.set_verify(true);
+        let mut models: Vec<Vec<isize>> = Vec::new();
+        for _ in 0..5
+        {
+            let model = solver.solve_with_assumptions(&[]).unwrap().unwrap();
+            assert!(!models.contains(&model));
+            assert_eq!(solver.value(model[7]), Some(true));
+            solver.add_clause(&model.iter().map(|l| -l).collect::<Vec<isize>>());
+            assert_eq!(solver.value(1), None);
+            models.push(model);
+        }
+        assert_eq!(solver.num_clauses(), 218 + 5);
+
+        //assumptions already implied open empty levels, the failed ones are only those in the conflict
+        let mut solver = Solver::new();
+        solver.add_clause(&[1]);
+        solver.add_clause(&[-1, -2, 3]);
+        solver.add_clause(&[-3, -4]);
+        assert!(solver.solve_with_assumptions(&[1, 5, 2, 4]).unwrap().is_none());
+        assert!(solver.failed(2) && solver.failed(4));
+        assert!(!solver.failed(1) && !solver.failed(5));
+        assert!(solver.solve_with_assumptions(&[-1]).unwrap().is_none());
+        assert!(solver.failed(-1));
+        assert_eq!(solver.solve_with_assumptions(&[1, 5, 2]).unwrap().map(|m| m.len()), Some(4));
+        assert_eq!(solver.value(3), Some(true));
+        assert_eq!(solver.value(5), None);
+
+        //a formula unsatisfiable without assumptions stays unsatisfiable
+        let mut solver = Solver::create("TestData/unsat30.cnf").unwrap();
+        assert!(solver.solve_with_assumptions(&[3]).unwrap().is_none());
+        assert!(solver.solve_with_assumptions(&[]).unwrap().is_none());
+        solver.add_clause(&[]);
+        assert!(solver.solve_with_assumptions(&[]).unwrap().is_none());
+
+        //the variables added after a 3-SAT conversion are numbered after the original ones
+        let mut solver = Solver::create_3sat("TestData/wide.cnf").unwrap();
+        solver.add_clause(&[-7, -1]);
+        solver.add_clause(&[7]);
+        let model = solver.solve_with_assumptions(&[]).unwrap().unwrap();
+        assert_eq!(model.len(), 7);
+        assert_eq!((model[0], model[6]), (-1, 7));
+        let model = solver.solve().unwrap().unwrap();
+        assert_eq!((model.len(), model[0], model[6]), (7, -1, 7));
+    }
+
+    #[test]
+    fn verify_falsified()
+    {
+        let mut solver = Solver::from_reader("p cnf 2 2\n1 2 0\n-1 2 0\n".as_bytes()).unwrap();
+        assert_eq!(solver.to_literals(&[Some(false), None]).unwrap(), vec![-1, -2]);
+        solver.set_verify(true);
+        assert!(matches!(solver.to_literals(&[Some(false), None]), Err(Error::ModelFalsified{index: 0, ..})));
+        assert_eq!(solver.to_literals(&[Some(false), Some(true)]).unwrap(), vec![-1, 2]);
+    }
+
+    #[test]
+    fn solve_models()
+    {
+        for file in ["TestData/test.cnf","TestData/solver20-0.cnf","TestData/uf50-01.cnf"]
+        {
+            let phi = Phi::from_file(file).unwrap();
+            let result = super::solve(&phi).unwrap();
+            assert!(phi.eval(&result));
         }
-        println!("SuccessRate: {}", 100.0 - (bad_results as f64 / 100.0 * 100.0));
-        assert_eq!(bad_results,0);
     }
 
     #[test]
@@ -450,67 +1764,168 @@ mod tests
         let phi = Phi::from_file("TestData/test.cnf").unwrap();
         let result = super::dpll(&phi);
         assert!(result.is_some());
+
+        let phi = Phi::from_file("TestData/uf50-02.cnf").unwrap();
+        let result = super::dpll(&phi).unwrap();
+        let assignment: Vec<bool> = result.iter().map(|x| x.unwrap()).collect();
+        assert!(phi.eval(&assignment));
+
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c2(1,2),
+            Clause::new_c2(-1,2),
+            Clause::new_c2(1,-2),
+            Clause::new_c2(-1,-2)
+        ]);
+        assert!(super::dpll(&phi).is_none());
     }
 
     #[test]
-    fn create_implications(){
-        let mut graph = DiGraph::<Literal,Literal>::new();
-        let x1 = Literal{index: 0, value: true, implicated: false, assigned: false};
-        let x2 = Literal{index: 1, value: true, implicated: false, assigned: false};
-        let x3 = Literal{index: 2, value: true, implicated: false, assigned: false};
-        let x4 = Literal{index: 3, value: true, implicated: false, assigned: false};
+    fn cdcl()
+    {
+        let phi = Phi::from_file("TestData/test.cnf").unwrap();
+        assert!(super::cdcl(&phi).is_some());
 
-        add_decision(x1,&mut graph);
-        add_decision(x2,&mut graph);
+        for file in ["TestData/uf50-01.cnf","TestData/uf50-02.cnf","TestData/uf150-02.cnf","TestData/uf175-01.cnf"]
+        {
+            let phi = Phi::from_file(file).unwrap();
+            let result = super::cdcl(&phi).unwrap();
+            let assignment: Vec<bool> = result.iter().map(|x| x.unwrap()).collect();
+            assert!(phi.eval(&assignment));
+        }
+    }
 
-        add_implication(x1,None,x3,&mut graph);
-        add_implication(x2,None,x3,&mut graph);
+    #[test]
+    fn cdcl_unsat()
+    {
+        let mut clauses = Vec::new();
+        for a in [1,-1]{
+            for b in [2,-2]{
+                for c in [3,-3]{
+                    clauses.push(Clause::new_c3(a,b,c));
+                }
+            }
+        }
+        let phi = Phi::from_clauses(clauses);
+        assert!(super::cdcl(&phi).is_none());
+    }
 
-        let (n3,_,_) = add_implication(x3,None,x4,&mut graph);
+    ///a heuristic that always makes the same choice, whatever the clauses left
+    struct Stubborn(Option<Literal>);
 
-        println!("{:?}",graph);
-        assert_eq!(graph.node_count(),4);
-        assert_eq!(graph.edge_count(),3);
-        assert_eq!(graph.neighbors_directed(n3,Incoming).count(),2)
+    impl BranchingHeuristic for Stubborn
+    {
+        fn pick(&mut self, _clauses: &[&[Literal]], _variables: &[usize], _assignments: &[Option<bool>]) -> Option<Literal>
+        {
+            self.0
+        }
     }
 
     #[test]
-    fn update_implications(){
-        let l1 = Literal{index: 0, value: true, implicated: false, assigned: true};
-        let l2 = Literal{index: 1, value: true, implicated: false, assigned: true};
-        let l3 = Literal{index: 2, value: true, implicated: false, assigned: false};
-        let l4 = Literal{index: 3, value: true, implicated: false, assigned: true};
+    fn misbehaving_heuristic()
+    {
+        let mut sat = Solver::from_reader("p cnf 4 5\n1 2 3 0\n-1 -2 4 0\n2 -3 -4 0\n-2 3 -4 0\n1 -3 4 0\n".as_bytes()).unwrap();
+        let unsat = Solver::from_reader("p cnf 4 8\n1 2 4 0\n1 -2 4 0\n-1 2 4 0\n-1 -2 4 0\n1 3 -4 0\n1 -3 -4 0\n-1 3 -4 0\n-1 -3 -4 0\n".as_bytes()).unwrap();
+        sat.set_verify(true);
+        //no choice, an assigned variable once the first branch is taken, a variable that does not exist
+        for choice in [None, Some(Literal::from_isize(-1)), Some(Literal::from_isize(9))]
+        {
+            let (solution, stats) = sat.solve_with_heuristic(&mut Stubborn(choice)).unwrap();
+            assert!(solution.is_some());
+            assert!(stats.decisions > 0);
+            assert_eq!(unsat.solve_with_heuristic(&mut Stubborn(choice)).unwrap().0, None);
+        }
+    }
 
-        let c1 = Clause::C3(l1,l2,l3);
-        let c2 = Clause::C2(l3,l4);
-        let phi = Phi{clauses: vec![c1,c2]};
-        let mut graph = DiGraph::<Literal,Literal>::new();
+    #[test]
+    fn lenient_empty_clause()
+    {
+        let (solver, warnings) = Solver::from_reader_with_mode("p cnf 1 1\n0\n".as_bytes(), ParseMode::Lenient).unwrap();
+        assert!(matches!(warnings[0], Error::EmptyClause{..}));
+        assert_eq!(solver.solve().unwrap(), None);
+        assert_eq!(solver.solve_dpll().unwrap(), None);
+        assert_eq!(solver.solve_cdcl().unwrap(), None);
 
-        update_implication_graph(&phi,&mut graph);
-        assert_eq!(graph.edge_count(),3);
+        let solver = Solver::from_reader("p cnf 0 0\n".as_bytes()).unwrap();
+        assert_eq!(solver.solve().unwrap(), Some(vec![]));
+        assert_eq!(solver.solve_dpll().unwrap(), Some(vec![]));
+        assert_eq!(solver.solve_cdcl().unwrap(), Some(vec![]));
     }
 
+    ///checks that every clause of a text proof is implied by unit propagation and that the proof ends with the empty clause
     #[test]
-    fn find_conflict(){
-        let l1 = Literal{index: 0, value: true, implicated: false, assigned: true};
-        let l2 = Literal{index: 1, value: true, implicated: false, assigned: true};
-        let l3 = Literal{index: 2, value: true, implicated: false, assigned: false};
-        let l4 = Literal{index: 3, value: true, implicated: false, assigned: false};
-        let nl3 = Literal{index: 2, value: false, implicated: false, assigned: false};
-        let nl4 = Literal{index: 3, value: false, implicated: false, assigned: false};
-
-        let c1 = Clause::C3(l1,l2,l3);
-        let c2 = Clause::C3(l1,l2,nl3);
-        let c3 = Clause::C2(l1,l4);
-        let c4 = Clause::C2(l2,nl4);
-        let phi = Phi{clauses: vec![c1,c2,c3,c4]};
-        let mut graph = DiGraph::<Literal,Literal>::new();
-
-        update_implication_graph(&phi,&mut graph);
-        let conflicts = find_conflicts(&graph);
-
-        assert_eq!(conflicts.len(),2);
-        assert_eq!(conflicts[0].index,l3.index);
-        assert_eq!(conflicts[1].index,l4.index);
+    fn proof()
+    {
+        let path = std::env::temp_dir().join(format!("colombini_sat_{}.drat", std::process::id()));
+        let path = path.to_str().unwrap();
+        let solver = Solver::create("TestData/unsat30.cnf").unwrap();
+        let searches: [Search; 3] =
+            [Solver::solve_with_proof, Solver::solve_dpll_with_proof, Solver::solve_cdcl_with_proof];
+        for (search, format) in searches.into_iter().zip([ProofFormat::Text, ProofFormat::Binary, ProofFormat::Text])
+        {
+            let mut proof = Proof::create(path, format).unwrap();
+            assert!(search(&solver, &mut proof).unwrap().0.is_none());
+            let additions = proof.additions() as usize;
+            proof.finish().unwrap();
+            let core = check_drat(&solver.phi, BufReader::new(File::open(path).unwrap()), format).unwrap();
+            assert!(core.lemmas.len() <= additions);
+            assert_eq!(core.lemmas.last(), Some(&Clause::empty()));
+        }
+
+        //a proof of some hundred lemmas on a formula that is not trivially refuted
+        let solver = Solver::create("TestData/solver8.cnf").unwrap();
+        let mut proof = Proof::create(path, ProofFormat::Binary).unwrap();
+        assert!(solver.solve_cdcl_with_proof(&mut proof).unwrap().0.is_none());
+        assert!(proof.additions() > 500);
+        proof.finish().unwrap();
+        let core = check_drat(&solver.phi, BufReader::new(File::open(path).unwrap()), ProofFormat::Binary).unwrap();
+        assert!(core.clauses.len() < solver.phi.clauses().len());
+        assert_eq!(core.lemmas.last(), Some(&Clause::empty()));
+
+        //every branch of the lookahead is refuted by 2-SAT formulas that unit propagation can not falsify
+        let mut clauses = Vec::new();
+        for a in [1,-1]{
+            for b in [2,-2]{
+                for c in [3,-3]{
+                    clauses.push(Clause::new_c3(a,b,c));
+                }
+            }
+        }
+        let phi = Phi::from_clauses(clauses);
+        let mut proof = Proof::create(path, ProofFormat::Text).unwrap();
+        assert!(super::solve_with_proof(&phi, &mut SolveStats::new(), &mut proof).is_none());
+        proof.finish().unwrap();
+        check_drat(&phi, BufReader::new(File::open(path).unwrap()), ProofFormat::Text).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        //satisfiable formulas give no empty clause
+        let solver = Solver::create("TestData/uf50-01.cnf").unwrap();
+        let mut proof = Proof::new(std::io::sink(), ProofFormat::Binary);
+        assert!(solver.solve_with_proof(&mut proof).unwrap().0.is_some());
     }
+
+    #[test]
+    fn stats()
+    {
+        let solver = Solver::create("TestData/uf50-01.cnf").unwrap();
+        let (solution, stats) = solver.solve_with_stats().unwrap();
+        assert!(solution.is_some());
+        assert!(stats.lookahead_calls > 0);
+        assert_eq!(stats.two_sat_calls, 2*stats.lookahead_calls);
+        assert!(stats.implications > 0);
+        assert!(stats.propagations >= 50);
+        assert!(stats.total_time >= stats.lookahead_time);
+        assert!(stats.lookahead_time >= stats.two_sat_time);
+
+        let (solution, stats) = solver.solve_cdcl_with_stats().unwrap();
+        assert!(solution.is_some());
+        assert!(stats.decisions > 0);
+        assert_eq!(stats.backtracks, stats.conflicts);
+        assert_eq!((stats.lookahead_calls, stats.implications), (0, 0));
+
+        let mut stats = SolveStats::new();
+        let phi = Phi::from_file("TestData/test.cnf").unwrap();
+        assert!(super::dpll_with_stats(&phi, &mut stats).is_some());
+        assert!(stats.propagations > 0);
+    }
+
 }
@@ -0,0 +1,155 @@
+use std::{fmt, time::Duration};
+
+/*
+Counters collected while searching, used to compare the algorithms and the heuristics on the same formulas.
+Every counter starts at zero and a search only updates the ones that make sense for it.
+*/
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolveStats
+{
+    ///branching decisions
+    pub decisions: u64,
+    ///literals propagated on the trail
+    pub propagations: u64,
+    ///clauses falsified during search
+    pub conflicts: u64,
+    ///decisions undone, by backtracking or by backjumping
+    pub backtracks: u64,
+    ///searches started again from the first decision level
+    pub restarts: u64,
+    ///clauses learned from conflicts or forced by the lookahead, and the ones deleted by the reduction policy
+    pub learned_clauses: u64,
+    pub deleted_clauses: u64,
+    ///variables probed by the lookahead
+    pub lookahead_calls: u64,
+    ///2-SAT formulas solved by the lookahead
+    pub two_sat_calls: u64,
+    ///binary implications found by the lookahead and stored, the ones already implied are not counted
+    pub implications: u64,
+    ///literals implied by the lookahead in every round
+    pub implied_per_round: Vec<usize>,
+    pub parse_time: Duration,
+    pub propagation_time: Duration,
+    ///time spent in the lookahead, 2-SAT solving included
+    pub lookahead_time: Duration,
+    pub two_sat_time: Duration,
+    ///time spent deriving learned clauses
+    pub analysis_time: Duration,
+    pub total_time: Duration,
+}
+
+impl SolveStats
+{
+    pub fn new() -> SolveStats
+    {
+        SolveStats::default()
+    }
+
+    ///returns the total number of literals implied by the lookahead
+    pub fn implied_literals(&self) -> usize
+    {
+        self.implied_per_round.iter().sum()
+    }
+
+    ///Serializes the statistics as a json object, times are in seconds
+    /// # Example
+    /// ```
+    /// use colombini_sat::stats::SolveStats;
+    ///
+    /// let mut stats = SolveStats::new();
+    /// stats.decisions = 3;
+    /// assert!(stats.to_json().contains("\"decisions\": 3"));
+    /// ```
+    pub fn to_json(&self) -> String
+    {
+        let rounds: Vec<String> = self.implied_per_round.iter().map(|r| r.to_string()).collect();
+        let mut json = String::from("{\n");
+        for (name, value) in self.counters()
+        {
+            json.push_str(&format!("  \"{name}\": {value},\n"));
+        }
+        json.push_str(&format!("  \"implied_per_round\": [{}],\n", rounds.join(", ")));
+        for (name, time) in self.times()
+        {
+            json.push_str(&format!("  \"{name}\": {:.6},\n", time.as_secs_f64()));
+        }
+        //the last comma is not allowed
+        json.truncate(json.len() - 2);
+        json.push_str("\n}");
+        json
+    }
+
+    fn counters(&self) -> [(&'static str, u64); 11]
+    {
+        [
+            ("decisions", self.decisions),
+            ("propagations", self.propagations),
+            ("conflicts", self.conflicts),
+            ("backtracks", self.backtracks),
+            ("restarts", self.restarts),
+            ("learned_clauses", self.learned_clauses),
+            ("deleted_clauses", self.deleted_clauses),
+            ("lookahead_calls", self.lookahead_calls),
+            ("two_sat_calls", self.two_sat_calls),
+            ("implications", self.implications),
+            ("implied_literals", self.implied_literals() as u64),
+        ]
+    }
+
+    fn times(&self) -> [(&'static str, Duration); 6]
+    {
+        [
+            ("parse_time", self.parse_time),
+            ("propagation_time", self.propagation_time),
+            ("lookahead_time", self.lookahead_time),
+            ("two_sat_time", self.two_sat_time),
+            ("analysis_time", self.analysis_time),
+            ("total_time", self.total_time),
+        ]
+    }
+}
+
+///Prints the statistics as a table
+impl fmt::Display for SolveStats
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        for (name, value) in self.counters()
+        {
+            writeln!(f, "{:<20}{:>14}", name, value)?;
+        }
+        writeln!(f, "{:<20}{:>14}", "rounds", self.implied_per_round.len())?;
+        for (name, time) in self.times()
+        {
+            writeln!(f, "{:<20}{:>13.3}s", name, time.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+    use super::SolveStats;
+
+    #[test]
+    fn report()
+    {
+        let mut stats = SolveStats::new();
+        stats.decisions = 4;
+        stats.implied_per_round = vec![2, 0, 3];
+        stats.total_time = Duration::from_millis(1500);
+        assert_eq!(stats.implied_literals(), 5);
+
+        let json = stats.to_json();
+        assert!(json.starts_with("{\n  \"decisions\": 4,"));
+        assert!(json.contains("\"implied_per_round\": [2, 0, 3],"));
+        assert!(json.ends_with("\"total_time\": 1.500000\n}"));
+
+        let table = stats.to_string();
+        assert!(table.lines().any(|l| l.starts_with("decisions") && l.ends_with(" 4")));
+        assert!(table.lines().any(|l| l.starts_with("total_time") && l.ends_with("1.500s")));
+    }
+}
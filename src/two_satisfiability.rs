@@ -1,63 +1,16 @@
 use std::collections::HashMap;
-use petgraph::{graph::{Graph, NodeIndex}, algo, prelude::DiGraph};
-use crate::{phi::Phi, error::Error, clause::Clause};
+use log::trace;
+use petgraph::{graph::NodeIndex, algo, prelude::DiGraph};
+use crate::{phi::Phi, clause::Literal, error::Error};
 
+///A value for every variable, None if the variable is free
+pub type Assignment = Vec<Option<bool>>;
 
-fn update_active_implications(solution: &mut Vec<bool>,problem: &Vec<(isize,isize)>,active_implications: &mut Vec<(isize,isize)>){
-    active_implications.clear();
-    for (i,xi_true) in solution.iter().enumerate(){
-        if *xi_true{
-            for (a,b) in problem.iter(){
-                if a.abs() == i as isize{
-                    active_implications.push((a.clone(),b.clone()));
-                }
-            }
-        }
-    }
-}
-
-fn determine_the_xj_that_have_just_become_active_and_inactive(active_implications: &mut Vec<(isize,isize)>,active_literals: &mut Vec<usize>)->(Vec<usize>,Vec<usize>){
-    let mut active = Vec::new();
-    let mut inactive = Vec::new();
-    for j in active_literals.iter(){
-        let mut active_j = false;
-        for (_a,b) in active_implications.iter(){
-            if b.abs() == *j as isize{
-                active_j = true;
-            }
-        }
-        if !active_j{
-            inactive.push((*j).clone());
-        }
-    }
-
-    for (_a,b) in active_implications.iter(){
-        let _b = b.abs() as usize;
-        if !active_literals.contains(&_b){
-            active.push(_b.clone());
-        }
-    }
-    (active, inactive)
-}
-
-fn remove_from_active_list_and_remember_predecessor(active_literals: &mut Vec<usize>, just_become_inactive: &Vec<usize>, n: &mut Vec<usize>){
-    let mut to_remove: Vec<usize> = Vec::new();
-    for j in just_become_inactive.iter(){
-        for i in active_literals.iter(){
-            if *i == *j{
-                to_remove.push(*i);
-                n.push(*i);
-            }
-        }
-    }
-    for i in to_remove.iter(){
-        active_literals.remove(*i);
-    }
-
-
-}
 
-pub fn create_graph(phi: &Phi)->Option<DiGraph<isize,isize>>{
+///Creates the implication graph of a 2-SAT formula, every clause (a or b) gives the edges -a -> b and -b -> a
+/// # Returns
+/// * The graph, or an error if phi has an empty clause or a clause with more than two unassigned literals
+pub fn create_graph(phi: &Phi)->Result<DiGraph<isize,isize>, Error>{
     let mut graph = DiGraph::<isize,isize>::new();
     let mut added_literals = HashMap::<isize,NodeIndex>::new();
 
@@ -68,392 +21,536 @@ pub fn create_graph(phi: &Phi)->Option<DiGraph<isize,isize>>{
     //check that all clauses in phi are 2-sat and
     //for each clause in phi, create a graph composed by each literal being a node 
     //and each clause impling two edges between the two literals in the clause
-    for clause in &rphi.clauses
+    for clause in rphi.clauses()
     {
-        match clause
+        match clause.literals.as_slice()
         {
-            Clause::C3(_,_,_) => { panic!("Not a 2-sat formula")},
-            Clause::C2(l1,l2) => {
+            [l1,l2] => {
                 for i in 0..2
                 {
                     let index1: isize = if i == 0 { -l1.as_isize() } else { -l2.as_isize() };
                     let index2: isize = if i == 0 { l2.as_isize() } else { l1.as_isize() };
 
-                    let mut n1 = added_literals.get(&index1).copied();
-                    match n1 {
-                        None => {
-                            let node = graph.add_node(index1);
-                            n1 = Some(node);
-                            added_literals.insert(index1, node);
-                        }
-                        Some(_) => {}
-                    }
-                    let mut n2 = added_literals.get(&index2).copied();
-                    match n2 {
-                        None => {
-                            let node = graph.add_node(index2);
-                            n2 = Some(node);
-                            added_literals.insert(index2, node);
-                        }
-                        Some(_) => {}
-                    }
-                    graph.add_edge(n1.unwrap(), n2.unwrap(), 0);
+                    let n1 = *added_literals.entry(index1).or_insert_with(|| graph.add_node(index1));
+                    let n2 = *added_literals.entry(index2).or_insert_with(|| graph.add_node(index2));
+                    graph.add_edge(n1, n2, 0);
                 }
             },
-            Clause::C1(_) => { unreachable!() },
-            Clause::Empty => { return None },
+            [_] => { unreachable!() },
+            [] => { return Err(Error::Unsatisfiable{reason: "found empty clause in 2-sat"}) },
+            literals => { return Err(Error::ClauseTooWide{at: None, width: literals.len(), max: 2}) },
         }
     }
-    Some(graph)
+    Ok(graph)
 }
 
-pub fn enumerate(og_solution: &Vec<Option<bool>>, phi_prime: &Phi){
-    //create a new phi that has all the literals that are true in solution inverted
-    let mut _added_to_graph = HashMap::<usize,NodeIndex>::new();
-    let mut solution = og_solution.clone();
-    let mut phi = phi_prime.clone();
-    let mut permutation_map = HashMap::<usize,usize>::new();
-    let mut literals = phi.get_variables();
-    literals.sort();
-
-    let _graph = create_graph(&phi);
-
-    for (i,literal) in literals.iter().enumerate(){
-        permutation_map.insert(i, literal.clone());
-        match solution[*literal]{
-            Some(true) => {phi.invert_literal(i);solution[*literal]=Some(false)},
-            Some(false) => {},
-            None => {}
-        }
-    }
-
-    let problem = Graph::<isize,isize>::new();
-    let mut _sorted: Vec<NodeIndex> = Vec::new();
-    match algo::toposort(&problem, None){
-        Ok(result) => {_sorted = result;},
-        Err(_) => {panic!("Not a DAG")}
-    }
-
-
-}
-
-pub fn _enumerate(solution: &mut Vec<bool>, active_literals: &mut Vec<usize>,active_implications: &mut Vec<(isize,isize)>, index: usize, depth: usize, solutions: &mut Vec<Vec<bool>>,problem: &Vec<(isize,isize)>){
-    let i = index;
-    solution[i]=true;
-    if depth % 2 == 0 {solutions.push(solution.clone());}
-    update_active_implications(solution, problem, active_implications);
-    let (mut m, inactive) = determine_the_xj_that_have_just_become_active_and_inactive(active_implications, active_literals);
-    let mut n: Vec<usize> = Vec::new();
-    remove_from_active_list_and_remember_predecessor(active_literals, &inactive, &mut n);
-    m.sort();
-    //merge m and active literals in order
-    for j in m.iter().rev(){
-        if *j==i {break};
-        active_literals.push(*j);
-        active_literals.sort();
-        _enumerate(solution, active_literals, active_implications, *j, depth+1, solutions, problem);
-    }
-    if depth %2 == 1 {solutions.push(solution.clone());}
-    solution[i]=false;
-    for j in m.iter(){
-        active_literals.push(*j);
-    }
-    active_literals.sort();
-    update_active_implications(solution, problem, active_implications);
+///Solves a 2-SAT formula
+/// # Arguments
+/// * `phi` - A formula where every clause has at most two unassigned literals
+/// * `n` - The number of variables
+/// # Returns
+/// * A satisfying assignment where every variable of phi has a value, or an error if phi is not satisfiable
+pub fn solve_2_sat(phi: &Phi,n:usize) -> Result<Vec<Option<bool>>, Error>
+{
+    solve_2_sat_forced(phi, n).map(|(_, model)| model)
 }
 
-pub fn _solve_2_sat(phi: &Phi) -> Result<Vec<Option<bool>>, Error>
+///Solves a 2-SAT formula keeping apart the values forced by unit propagation
+/// # Arguments
+/// * `phi` - A formula where every clause has at most two unassigned literals
+/// * `n` - The number of variables
+/// # Returns
+/// * The values forced by the unit clauses of phi and a satisfying assignment that extends them
+pub fn solve_2_sat_forced(phi: &Phi,n:usize) -> Result<(Assignment, Assignment), Error>
 {
-    let mut graph = Graph::<isize,i8>::new();
-    let mut added_literals = HashMap::<isize,NodeIndex>::new();
-
     //remove unit clauses
-    let mut assignments: Vec<Option<bool>> = vec![None;phi.vars()];
-    let rphi = phi.autoreduce_with_assignments(&mut assignments);
-    //check that all clauses in phi are 2-sat and
-    //for each clause in phi, create a graph composed by each literal being a node 
-    //and each clause impling two edges between the two literals in the clause
-    for clause in &rphi.clauses
+    let mut assignments: Vec<Option<bool>> = vec![None;n];
+    let mut rphi = phi.autoreduce_with_assignments(&mut assignments);
+    rphi = rphi.adapt();
+    //create implication graph
+    let graph = create_graph(&rphi)?;
+
+    //find all completely connected groups
+    let sccs: Vec<Vec<NodeIndex>> = algo::tarjan_scc(&graph);
+    if find_contradiction(&graph, &sccs).is_some()
     {
-        match clause
-        {
-            Clause::C3(_,_,_) => { return Err(Error::new("Not a 2-sat formula").into()) }
-            Clause::C2(l1,l2) => {
+        return Err(Error::Unsatisfiable{reason: "found a node with a completely connected path to its negation"});
+    }
 
-                for i in 0..2
-                {
-                    let index1: isize = if i == 0 { -l1.as_isize() } else { l1.as_isize() };
-                    let index2 = if i == 0 { l2.as_isize() } else { -l2.as_isize() };
-
-                    let mut n1 = added_literals.get(&index1).copied();
-                    match n1 {
-                        None => {
-                            let node = graph.add_node(index1);
-                            n1 = Some(node);
-                            added_literals.insert(index1, node);
-                        }
-                        Some(_) => {}
-                    }
-                    let mut n2 = added_literals.get(&index2).copied();
-                    match n2 {
-                        None => {
-                            let node = graph.add_node(index2);
-                            n2 = Some(node);
-                            added_literals.insert(index2, node);
-                        }
-                        Some(_) => {}
-                    }
-                    graph.add_edge(n1.unwrap(), n2.unwrap(), 1);
-                }
-            },
-            Clause::C1(_) => { unreachable!() },
-            Clause::Empty => { return Err(Error::new("Not satisfiable, empty clause given.").into()) },
+    /*the groups are returned in reverse topological order so, a satisfyng assignment 
+    can be found by making true the first literal of every variable met in this order*/
+    let mut model = assignments.clone();
+    for scc in &sccs
+    {
+        for node in scc
+        {
+            let lit = Literal::from_isize(graph[*node]);
+            if model[lit.index].is_none()
+            {
+                model[lit.index] = Some(lit.value);
+            }
         }
     }
 
-    //find all completely connected groups
-    let mut sccs = algo::tarjan_scc(&graph);
-    sccs.reverse();
-    //explore the groups and find if there is a node with a completely connected (two way) path to its negation
-    /*the groups are returned in reverse topological order so, a satisfyng assignment 
-    can be found by assigning the variables in the reverse order of the groups*/
+    trace!("2-sat phi = {rphi}");
+    trace!("2-sat model = {:?}", model);
+    Ok((assignments, model))
+}
+
+
+///explores the groups and finds a literal with a completely connected (two way) path to its negation
+fn find_contradiction(graph: &DiGraph<isize,isize>, sccs: &[Vec<NodeIndex>]) -> Option<isize>
+{
     for scc in sccs
     {
-        for node in &scc
+        let mut added_literals = HashMap::<isize,NodeIndex>::new();
+        for node in scc
         {
             let index: isize = graph[*node];
-            let mut arr_index = index.abs() as usize;
-            arr_index -= 1;
-            match assignments[arr_index]{
-                Some(_) => {},
-                None => {
-                if index > 0{
-                    assignments[arr_index] = Some(true);
-                }else{
-                    assignments[arr_index] = Some(false);
-                }}
-            }
+            added_literals.insert(index, *node);
             let negation = -index;
             let negation_node = added_literals.get(&negation).copied();
             if let Some(negation_node) = negation_node
             {
                 if scc.contains(&negation_node)
                 {
-                    return Err(Error::new("Not satisfiable, found a node with a completely connected path to its negation."));
+                    return Some(index);
                 }
             }
         }
     }
+    None
+}
 
-    let mut phi_ = rphi.clone();
-    let mut new_assignments = assignments.clone();
-    for (index,assignment) in assignments.iter().enumerate(){
-        match assignment{
-            Some(true) => {
-                phi_.invert_literal(index);
-                new_assignments[index] = Some(false);
-            },
-            Some(false) => {},
-            None => {}
+///Finds why a 2-SAT formula is not satisfiable, used to explain the refutation to a proof checker
+/// # Arguments
+/// * `phi` - A formula where every clause has at most two unassigned literals
+/// # Returns
+/// * A literal that implies its negation and is implied by it, None if phi is satisfiable
+///   or if unit propagation alone falsifies a clause
+/// # Example
+/// ```
+/// use colombini_sat::{phi::Phi, clause::Clause, two_satisfiability::contradiction};
+/// let phi = Phi::from_clauses(vec![Clause::new_c2(1,2), Clause::new_c2(-1,2), Clause::new_c2(1,-2), Clause::new_c2(-1,-2)]);
+/// assert!(contradiction(&phi).is_some());
+/// ```
+pub fn contradiction(phi: &Phi) -> Option<isize>
+{
+    let graph = create_graph(phi).ok()?;
+    find_contradiction(&graph, &algo::tarjan_scc(&graph))
+}
+
+///Lazy iterator over every satisfying assignment of a 2-SAT formula.\
+///Variables are decided in ascending order, true first, and every decision is followed by all its implications,
+///so a partial assignment without conflicts can always be extended and each model is found in polynomial time.
+#[derive(Default)]
+pub struct Models
+{
+    graph: DiGraph<isize,isize>,
+    nodes: HashMap<isize,NodeIndex>,
+    //the variables of phi that are not forced by its unit clauses
+    variables: Vec<usize>,
+    assignment: Assignment,
+    //indices of the variables assigned since the start of the enumeration
+    trail: Vec<usize>,
+    //for every decision, its position in variables, the value tried and the trail length before it
+    decisions: Vec<(usize, bool, usize)>,
+    position: usize,
+    emitted: bool,
+    done: bool,
+}
+
+impl Models
+{
+    ///Assigns the literal and every literal it implies
+    /// # Returns
+    /// * false if an implied literal was already assigned to the opposite value
+    fn imply(&mut self, index: usize, value: bool) -> bool
+    {
+        let lit = Literal{index, value, implicated: false, assigned: false};
+        let mut stack: Vec<isize> = vec![lit.as_isize()];
+        while let Some(l) = stack.pop()
+        {
+            let lit = Literal::from_isize(l);
+            match self.assignment[lit.index]
+            {
+                //the implications of an assigned literal are already assigned
+                Some(v) if v == lit.value => continue,
+                Some(_) => return false,
+                None => {
+                    self.assignment[lit.index] = Some(lit.value);
+                    self.trail.push(lit.index);
+                }
+            }
+            if let Some(node) = self.nodes.get(&l)
+            {
+                stack.extend(self.graph.neighbors(*node).map(|n| self.graph[n]));
+            }
         }
+        true
     }
 
-    println!("phi = {}",rphi);
-    print!("assignments = ");
-    for (i, a) in assignments.iter().enumerate() {
-        let index = i + 1;
-        match a {
-            Some(true) => print!("X_{index} = T, "),
-            Some(false) => print!("X_{index} = F, "),
-            None => (),
+    fn undo(&mut self, trail_len: usize)
+    {
+        for index in self.trail.drain(trail_len..)
+        {
+            self.assignment[index] = None;
         }
     }
-    println!();
-    println!("phi_ = {}",phi_);
-    print!("new_assignments = ");
-    for (i, a) in new_assignments.iter().enumerate() {
-        let index = i + 1;
-        match a {
-            Some(true) => print!("X_{index} = T, "),
-            Some(false) => print!("X_{index} = F, "),
-            None => (),
+
+    ///Flips the deepest decision still set to true, undoing everything assigned after it
+    /// # Returns
+    /// * false if every decision was already tried with both values
+    fn backtrack(&mut self) -> bool
+    {
+        while let Some((position, value, trail_len)) = self.decisions.pop()
+        {
+            self.undo(trail_len);
+            if value
+            {
+                self.decisions.push((position, false, trail_len));
+                self.position = position;
+                if self.imply(self.variables[position], false) { return true; }
+            }
         }
+        false
     }
-    println!();
-
-    let mut assignments: Vec<Option<bool>> = vec![None;phi.vars()];
 
-    for clause in &phi_.clauses
+    ///returns the values shared by every model, None for the variables that change between models
+    pub fn fixed(&mut self) -> Assignment
     {
-        match clause
+        self.undo(0);
+        self.decisions.clear();
+        let mut fixed = self.assignment.clone();
+        if self.done { return fixed; }
+        /*an assignment closed under implication that has no conflicts can always be extended to a model,
+        so a variable is fixed exactly when one of its values leads to a conflict*/
+        for i in 0..self.variables.len()
         {
-            Clause::C3(_,_,_) => { return Err(Error::new("Not a 2-sat formula").into()) }
-            Clause::C2(l1,l2) => {
-
-                for i in 0..2
-                {
-                    let index1: isize = if i == 0 { -l1.as_isize() } else { l1.as_isize() };
-                    let index2 = if i == 0 { l2.as_isize() } else { -l2.as_isize() };
-
-                    let mut n1 = added_literals.get(&index1).copied();
-                    match n1 {
-                        None => {
-                            let node = graph.add_node(index1);
-                            n1 = Some(node);
-                            added_literals.insert(index1, node);
-                        }
-                        Some(_) => {}
-                    }
-                    let mut n2 = added_literals.get(&index2).copied();
-                    match n2 {
-                        None => {
-                            let node = graph.add_node(index2);
-                            n2 = Some(node);
-                            added_literals.insert(index2, node);
-                        }
-                        Some(_) => {}
-                    }
-                    graph.add_edge(n1.unwrap(), n2.unwrap(), 1);
-                }
-            },
-            Clause::C1(_) => { unreachable!() },
-            Clause::Empty => { return Err(Error::new("Not satisfiable, empty clause given.").into()) },
+            let index = self.variables[i];
+            let can_be_true = self.imply(index, true);
+            self.undo(0);
+            let can_be_false = self.imply(index, false);
+            self.undo(0);
+            if can_be_true != can_be_false { fixed[index] = Some(can_be_true); }
         }
+        self.position = 0;
+        self.emitted = false;
+        fixed
     }
+}
 
-    let mut sccs = algo::tarjan_scc(&graph);
-    sccs.reverse();
-    //explore the groups and find if there is a node with a completely connected (two way) path to its negation
-    /*the groups are returned in reverse topological order so, a satisfyng assignment 
-    can be found by assigning the variables in the reverse order of the groups*/
-    for scc in sccs
+impl Iterator for Models
+{
+    type Item = Assignment;
+
+    fn next(&mut self) -> Option<Assignment>
     {
-        for node in &scc
+        if self.done { return None; }
+        if self.emitted && !self.backtrack()
         {
-            let index: isize = graph[*node];
-            let mut arr_index = index.abs() as usize;
-            arr_index -= 1;
-            match assignments[arr_index]{
-                Some(_) => {},
-                None => {
-                if index > 0{
-                    assignments[arr_index] = Some(true);
-                }else{
-                    assignments[arr_index] = Some(false);
-                }}
-            }
-            let negation = -index;
-            let negation_node = added_literals.get(&negation).copied();
-            if let Some(negation_node) = negation_node
+            self.done = true;
+            return None;
+        }
+        loop
+        {
+            let next = self.variables[self.position..].iter().position(|v| self.assignment[*v].is_none());
+            match next
             {
-                if scc.contains(&negation_node)
-                {
-                    return Err(Error::new("Not satisfiable, found a node with a completely connected path to its negation."));
+                None => {
+                    self.emitted = true;
+                    return Some(self.assignment.clone());
+                },
+                Some(offset) => {
+                    self.position += offset;
+                    self.decisions.push((self.position, true, self.trail.len()));
+                    if !self.imply(self.variables[self.position], true) && !self.backtrack()
+                    {
+                        self.done = true;
+                        return None;
+                    }
                 }
             }
         }
     }
+}
 
-    print!("After shit algorithm: ");
-    for (i, a) in assignments.iter().enumerate() {
-        let index = i + 1;
-        match a {
-            Some(true) => print!("X_{index} = T, "),
-            Some(false) => print!("X_{index} = F, "),
-            None => (),
-        }
-    }
-    println!();
-    
-    Ok(assignments)
+///Enumerates the satisfying assignments of a 2-SAT formula
+/// # Arguments
+/// * `phi` - A formula where every clause has at most two unassigned literals
+/// * `n` - The number of variables
+/// # Returns
+/// * An iterator over the models of phi, variables that do not appear in phi are left to None
+/// # Example
+/// ```
+/// use colombini_sat::{phi::Phi, clause::Clause, two_satisfiability::enumerate};
+/// let phi = Phi::from_clauses(vec![Clause::new_c2(1,2), Clause::new_c2(-1,-2)]);
+/// let models: Vec<_> = enumerate(&phi, 2).collect();
+/// assert_eq!(models, vec![vec![Some(true), Some(false)], vec![Some(false), Some(true)]]);
+/// ```
+pub fn enumerate(phi: &Phi, n: usize) -> Models
+{
+    models(phi, n).unwrap_or(Models{done: true, ..Models::default()})
 }
 
-pub fn solve_2_sat(phi: &Phi,n:usize) -> Result<Vec<Option<bool>>, Error>
+fn models(phi: &Phi, n: usize) -> Result<Models, Error>
 {
-    //remove unit clauses
-    let mut assignments: Vec<Option<bool>> = vec![None;n];
-    let mut rphi = phi.autoreduce_with_assignments(&mut assignments);
-    rphi = rphi.adapt();
-    //create implication graph
-    let implications = create_graph(&rphi);
-    let graph:DiGraph<isize,isize>;
+    let mut variables = phi.get_variables();
+    //unit clauses are not part of the graph, their values are shared by every model
+    let mut assignment: Assignment = vec![None;n.max(phi.vars())];
+    let rphi = phi.autoreduce_with_assignments(&mut assignment).adapt();
+    solve_2_sat(phi, assignment.len())?;
+    let graph = create_graph(&rphi)?;
+    let nodes: HashMap<isize,NodeIndex> = graph.node_indices().map(|node| (graph[node], node)).collect();
+    variables.retain(|v| assignment[*v].is_none());
+    Ok(Models{
+        graph,
+        nodes,
+        variables,
+        assignment,
+        trail: Vec::new(),
+        decisions: Vec::new(),
+        position: 0,
+        emitted: false,
+        done: false,
+    })
+}
 
-    match implications{
-        Some(g)=>{graph = g}
-        None => {return Err(Error::new("Not satisfiable, found empty clause in 2-sat"))}
-    }
+///Finds the values shared by every satisfying assignment of a 2-SAT formula
+/// # Arguments
+/// * `phi` - A formula where every clause has at most two unassigned literals
+/// * `n` - The number of variables
+/// # Returns
+/// * The fixed values, None for the variables that are free or change between models, or an error if phi is not satisfiable or not 2-SAT
+pub fn fixed_2_sat(phi: &Phi, n: usize) -> Result<Assignment, Error>
+{
+    Ok(models(phi, n)?.fixed())
+}
 
-    //find all completely connected groups
-    let mut sccs: Vec<Vec<NodeIndex>> = algo::tarjan_scc(&graph);
-    sccs.reverse();
-    //explore the groups and find if there is a node with a completely connected (two way) path to its negation
-    /*the groups are returned in reverse topological order so, a satisfyng assignment 
-    can be found by assigning the variables in the reverse order of the groups*/
-    for scc in sccs
+///Same as fixed_2_sat for a formula given as pairs of literals, a unit clause being a pair of the same literal.\
+///Only the variables of the clauses are numbered, so the small formulas probed by the lookahead are solved
+///without building a Phi or a graph as big as the whole formula.
+/// # Returns
+/// * The literals true in every model in ascending order of variable, or an error if the formula is not satisfiable
+/// # Example
+/// ```
+/// use colombini_sat::{clause::Literal, two_satisfiability::fixed_literals};
+/// let lit = Literal::from_isize;
+/// let clauses = [(lit(1), lit(2)), (lit(-1), lit(2)), (lit(-2), lit(3))];
+/// assert_eq!(fixed_literals(&clauses).unwrap(), vec![lit(2), lit(3)]);
+/// assert!(fixed_literals(&[(lit(1), lit(1)), (lit(-1), lit(-1))]).is_err());
+/// ```
+pub fn fixed_literals(clauses: &[(Literal, Literal)]) -> Result<Vec<Literal>, Error>
+{
+    let mut variables: Vec<usize> = clauses.iter().flat_map(|(a, b)| [a.index, b.index]).collect();
+    variables.sort_unstable();
+    variables.dedup();
+    let code = |lit: &Literal| 2*variables.binary_search(&lit.index).unwrap() + lit.value as usize;
+    //the implication graph in adjacency arrays, every clause (a or b) gives the edges -a -> b and -b -> a
+    let mut edges: Vec<(usize, usize)> = clauses.iter()
+        .flat_map(|(a, b)| [(code(a) ^ 1, code(b)), (code(b) ^ 1, code(a))])
+        .collect();
+    edges.sort_unstable();
+    let mut start = vec![0; 2*variables.len() + 1];
+    for (from, _) in &edges
     {
-        let mut added_literals = HashMap::<isize,NodeIndex>::new();
-        for node in &scc
+        start[from + 1] += 1;
+    }
+    for i in 1..start.len()
+    {
+        start[i] += start[i - 1];
+    }
+    //the literals reached by the current closure are marked with its number, so the marks are never cleared
+    let mut marks = vec![0usize; 2*variables.len()];
+    let mut mark = 0;
+    let mut reached = Vec::new();
+    //the closure of the literals under implication, false if it reaches both values of a variable
+    let mut closure = |from: &[usize]| -> bool {
+        mark += 1;
+        reached.clear();
+        for l in from
         {
-            let index: isize = graph[*node];
-            added_literals.insert(index, *node);
-            let negation = -index;
-            let negation_node = added_literals.get(&negation).copied();
-            if let Some(negation_node) = negation_node
+            if marks[*l] != mark
             {
-                if scc.contains(&negation_node)
+                marks[*l] = mark;
+                reached.push(*l);
+            }
+        }
+        let mut next = 0;
+        while next < reached.len()
+        {
+            let l = reached[next];
+            next += 1;
+            if marks[l ^ 1] == mark { return false; }
+            for (_, to) in &edges[start[l]..start[l + 1]]
+            {
+                if marks[*to] != mark
                 {
-                    return Err(Error::new("Not satisfiable, found a node with a completely connected path to its negation."));
+                    marks[*to] = mark;
+                    reached.push(*to);
                 }
             }
         }
+        true
+    };
+    let units: Vec<usize> = clauses.iter().filter(|(a, b)| a.index == b.index && a.value == b.value).map(|(a, _)| code(a)).collect();
+    if !closure(&units)
+    {
+        return Err(Error::Unsatisfiable{reason: "found empty clause in 2-sat"});
     }
-
-    Ok(assignments)
+    //a value that leads to a conflict is never part of a model, the units included
+    let mut fixed = Vec::new();
+    for (i, index) in variables.iter().enumerate()
+    {
+        let can_be_true = closure(&[2*i + 1]);
+        let can_be_false = closure(&[2*i]);
+        match (can_be_true, can_be_false)
+        {
+            (false, false) => return Err(Error::Unsatisfiable{reason: "found a node with a completely connected path to its negation"}),
+            (true, true) => {},
+            (value, _) => fixed.push(Literal{index: *index, value, implicated: false, assigned: false})
+        }
+    }
+    Ok(fixed)
 }
 
-
-
 #[cfg(test)]
 mod tests{
-    use crate::{phi::Phi, clause::Clause, two_satisfiability::create_graph};
+    use crate::{phi::Phi, clause::{Clause, Literal}, error::Error, heuristics::Rng, two_satisfiability::create_graph};
 
     #[test]
     fn solve_2_sat()
     {
-        let phi = Phi{
-            clauses: vec![
-                Clause::new_c2(1,-2),
-                Clause::new_c2(-1,2),
-                Clause::new_c2(-1,-2),
-                Clause::new_c2(1,-3),
-            ]
-        };
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c2(1,-2),
+            Clause::new_c2(-1,2),
+            Clause::new_c2(-1,-2),
+            Clause::new_c2(1,-3),
+        ]);
+        let result = super::solve_2_sat(&phi,phi.vars());
+        assert_eq!(result.unwrap(), vec![Some(false), Some(false), Some(false)]);
+
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c2(1,-2),
+            Clause::new_c2(1,2),
+            Clause::new_c2(-1,2),
+            Clause::new_c2(-1,-2),
+            Clause::new_c2(1,-3),
+        ]);
         let result = super::solve_2_sat(&phi,phi.vars());
-        assert!(result.is_ok());
-
-        let phi = Phi{
-            clauses: vec![
-                Clause::new_c2(1,-2),
-                Clause::new_c2(1,2),
-                Clause::new_c2(-1,2),
-                Clause::new_c2(-1,-2),
-                Clause::new_c2(1,-3),
-            ]
-        };
+        assert!(matches!(result, Err(Error::Unsatisfiable{..})));
+
+        let phi = Phi::from_clauses(vec![Clause::new_c2(1,-2), Clause::new_c3(1,2,3)]);
         let result = super::solve_2_sat(&phi,phi.vars());
-        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::ClauseTooWide{at: None, width: 3, max: 2})));
+    }
+
+    #[test]
+    fn model()
+    {
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c2(1,2),
+            Clause::new_c2(-1,3),
+            Clause::new_c2(-3,4),
+            Clause::new_c2(-4,-2),
+            Clause::new_c2(5,-6),
+            Clause::new_c2(6,-5),
+            Clause::new_c2(-5,2),
+            Clause::new_c1(7),
+            Clause::new_c3(-7,8,-2),
+        ]);
+        let (forced, model) = super::solve_2_sat_forced(&phi,phi.vars()).unwrap();
+        assert_eq!(forced[6], Some(true));
+        assert!(forced[0].is_none());
+        let model: Vec<bool> = model.iter().map(|v| v.unwrap()).collect();
+        assert!(phi.eval(&model));
+    }
+
+    #[test]
+    fn enumerate()
+    {
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c2(1,2),
+            Clause::new_c2(-2,3),
+            Clause::new_c1(-4),
+            Clause::new_c2(4,5),
+        ]);
+        let models: Vec<Vec<Option<bool>>> = super::enumerate(&phi, 6).collect();
+        //x1 or x2, x2 -> x3, x4 and x5 forced, x6 is not in phi
+        let mut expected = Vec::new();
+        for (x1, x2, x3) in [(true,true,true),(true,false,true),(true,false,false),(false,true,true)]
+        {
+            expected.push(vec![Some(x1), Some(x2), Some(x3), Some(false), Some(true), None]);
+        }
+        assert_eq!(models, expected);
+        for model in &models
+        {
+            let model: Vec<bool> = model.iter().map(|v| v.unwrap_or(false)).collect();
+            assert!(phi.eval(&model));
+        }
+
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c2(1,2),
+            Clause::new_c2(1,-2),
+            Clause::new_c2(-1,2),
+            Clause::new_c2(-1,-2),
+        ]);
+        assert_eq!(super::enumerate(&phi, 2).count(), 0);
+        assert!(super::fixed_2_sat(&phi, 2).is_err());
+    }
+
+    #[test]
+    fn fixed()
+    {
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c2(1,2),
+            Clause::new_c2(1,-2),
+            Clause::new_c2(-1,3),
+            Clause::new_c2(4,5),
+            Clause::new_c1(-6),
+        ]);
+        let fixed = super::fixed_2_sat(&phi, phi.vars()).unwrap();
+        assert_eq!(fixed, vec![Some(true), None, Some(true), None, None, Some(false)]);
+
+        //the fixed values are the ones shared by all the models
+        let mut models = super::enumerate(&phi, phi.vars());
+        let first = models.next().unwrap();
+        let shared = models.fold(first, |acc, model| acc.iter().zip(model.iter()).map(|(a,b)| if a == b { *a } else { None }).collect());
+        assert_eq!(shared, fixed);
+    }
+
+    #[test]
+    fn fixed_literals()
+    {
+        //random formulas of units and pairs over 6 variables, satisfiable or not, agree with fixed_2_sat
+        let mut rng = Rng::new(11);
+        let mut lit = || Literal::from_isize((rng.below(6) + 1) as isize * if rng.below(2) == 0 { 1 } else { -1 });
+        for _ in 0..500
+        {
+            let clauses: Vec<(Literal, Literal)> = (0..8).map(|i| { let a = lit(); (a, if i % 4 == 0 { a } else { lit() }) }).collect();
+            let phi = Phi::from_clauses(clauses.iter().map(|(a, b)| Clause::new(if a == b { vec![*a] } else { vec![*a, *b] })).collect());
+            match (super::fixed_2_sat(&phi, 6), super::fixed_literals(&clauses))
+            {
+                (Ok(fixed), Ok(literals)) => {
+                    let expected: Vec<Literal> = fixed.iter().enumerate()
+                        .filter_map(|(index, v)| v.map(|value| Literal{index, value, implicated: false, assigned: false}))
+                        .collect();
+                    assert_eq!(literals, expected);
+                },
+                (Err(_), Err(_)) => {},
+                (expected, found) => panic!("{phi}: fixed_2_sat {expected:?}, fixed_literals {found:?}")
+            }
+        }
     }
 
     #[test]
     fn graph(){
-        let phi = Phi{
-            clauses: vec![
-                Clause::new_c2(1,-2),
-                Clause::new_c2(-1,2),
-                Clause::new_c2(-1,-2),
-                Clause::new_c2(1,-3),
-            ]
-        };
+        let phi = Phi::from_clauses(vec![
+            Clause::new_c2(1,-2),
+            Clause::new_c2(-1,2),
+            Clause::new_c2(-1,-2),
+            Clause::new_c2(1,-3),
+        ]);
 
         let graph = create_graph(&phi);
         println!("{:?}",graph);
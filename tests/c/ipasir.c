@@ -0,0 +1,67 @@
+/*
+Checks the C interface against the cdylib, compiled and run by tests/ipasir.rs with the system compiler:
+cargo test --test ipasir
+*/
+#include <assert.h>
+#include <stdio.h>
+#include "ipasir.h"
+
+static void add (void * solver, const int32_t * clause)
+{
+    while (*clause) ipasir_add (solver, *clause++);
+    ipasir_add (solver, 0);
+}
+
+/* stops the search at the first conflict only */
+static int terminate (void * data)
+{
+    int * calls = data;
+    return ++*calls == 1;
+}
+
+int main ()
+{
+    void * solver = ipasir_init ();
+    printf ("%s\n", ipasir_signature ());
+
+    /* a chain of implications 1 -> 2 -> ... -> 10 */
+    for (int32_t i = 1; i < 10; i++)
+    {
+        int32_t clause[] = {-i, i + 1, 0};
+        add (solver, clause);
+    }
+    ipasir_assume (solver, 1);
+    ipasir_assume (solver, -10);
+    ipasir_assume (solver, 5);
+    assert (ipasir_solve (solver) == 20);
+    assert (ipasir_failed (solver, -10));
+    assert (ipasir_failed (solver, 1) || ipasir_failed (solver, 5));
+
+    ipasir_assume (solver, 1);
+    assert (ipasir_solve (solver) == 10);
+    for (int32_t i = 1; i <= 10; i++) assert (ipasir_val (solver, i) == i);
+
+    /* the formula grows between the calls */
+    int32_t unit[] = {-7, 0};
+    add (solver, unit);
+    assert (ipasir_solve (solver) == 10);
+    assert (ipasir_val (solver, 1) == -1);
+    ipasir_assume (solver, 6);
+    assert (ipasir_solve (solver) == 20);
+    assert (ipasir_failed (solver, 6));
+
+    ipasir_release (solver);
+
+    /* every assignment of 1 and 2 is falsified, the first call is terminated at its first conflict */
+    solver = ipasir_init ();
+    int32_t clauses[][3] = {{1, 2, 0}, {1, -2, 0}, {-1, 2, 0}, {-1, -2, 0}};
+    for (int i = 0; i < 4; i++) add (solver, clauses[i]);
+    int calls = 0;
+    ipasir_set_terminate (solver, &calls, terminate);
+    assert (ipasir_solve (solver) == 0);
+    assert (ipasir_solve (solver) == 20);
+    assert (calls == 2);
+    ipasir_release (solver);
+    printf ("ok\n");
+    return 0;
+}
@@ -0,0 +1,49 @@
+use std::{env, fs, path::Path, process::Command};
+
+/*
+Checks the C interface from the outside: include/ipasir.h must be the header cbindgen generates from src/ipasir.rs
+with cbindgen.toml, and tests/c/ipasir.c, compiled by the system compiler against the cdylib, must pass.
+*/
+
+///fails when the committed header differs from the generated one, COLOMBINI_UPDATE_HEADER=1 rewrites it
+#[test]
+fn header()
+{
+    let dir = env!("CARGO_MANIFEST_DIR");
+    let config = cbindgen::Config::from_file(format!("{dir}/cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::generate_with_config(dir, config).unwrap().write(&mut generated);
+    let path = Path::new(dir).join("include/ipasir.h");
+    if env::var_os("COLOMBINI_UPDATE_HEADER").is_some()
+    {
+        fs::write(&path, &generated).unwrap();
+    }
+    let committed = fs::read(&path).unwrap();
+    assert!(committed == generated, "include/ipasir.h is out of date, regenerate it with COLOMBINI_UPDATE_HEADER=1 cargo test --test ipasir");
+}
+
+///compiles the C test against the cdylib built with the tests and runs it
+#[test]
+fn c_interface()
+{
+    let dir = env!("CARGO_MANIFEST_DIR");
+    //the test binaries are in target/<profile>/deps, the cdylib in target/<profile>, cargo test only builds the rlib
+    let profile = env::current_exe().unwrap().parent().and_then(Path::parent).unwrap().to_path_buf();
+    let mut build = Command::new(env!("CARGO"));
+    build.args(["build", "--quiet", "--lib", "--manifest-path", &format!("{dir}/Cargo.toml")]);
+    if profile.ends_with("release") { build.arg("--release"); }
+    assert!(build.status().unwrap().success(), "the cdylib does not build");
+    let lib = profile.display().to_string();
+    let exe = Path::new(env!("CARGO_TARGET_TMPDIR")).join("ipasir_test");
+    let status = Command::new("cc")
+        .arg(format!("-I{dir}/include"))
+        .arg(format!("{dir}/tests/c/ipasir.c"))
+        .args([format!("-L{lib}"), "-lcolombini_sat".to_string(), format!("-Wl,-rpath,{lib}")])
+        .arg("-o").arg(&exe)
+        .status()
+        .expect("the C test needs the system compiler, cc");
+    assert!(status.success(), "tests/c/ipasir.c does not compile");
+    let output = Command::new(&exe).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("ok\n"));
+}